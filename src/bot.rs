@@ -0,0 +1,161 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    core::{Action, GameState, Location, Minefield, PendingCommand, State},
+    solver::{mine_probabilities, SolverBackend},
+};
+
+mod simulator;
+
+pub use simulator::*;
+
+/// Probabilities closer than this to 0 or 1 are treated as certain.
+const CERTAINTY: f64 = 1e-9;
+
+/// The outcome of a game played by [`autoplay`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameReport {
+    pub won: bool,
+    /// Number of cells that were revealed without being known to be safe.
+    pub guesses: usize,
+    /// Time the bot needed for the whole game.
+    pub duration: Duration,
+}
+
+/// Plays the game to the end, starting with a click on `first_click`.
+///
+/// Every cell the `backend` deduces is revealed or marked. If it is stuck, the exact mine
+/// probabilities are consulted, which also take the global mine count into account. Only if
+/// no cell is certain either, the cell with the lowest mine probability is revealed as a guess.
+/// If the probabilities cannot be computed, the first hidden cell is revealed instead.
+pub fn autoplay(
+    game: &mut Minefield,
+    first_click: Location,
+    backend: &dyn SolverBackend,
+) -> GameReport {
+    let start = Instant::now();
+    let mut guesses = 0;
+    let mut solve = backend.session();
+    game.execute(PendingCommand::new(first_click, Action::Reveal));
+
+    while let GameState::InProgress { .. } = game.state() {
+        let (safe, mines) = solve(game);
+        let mut commands = commands(game, safe, mines);
+        if commands.is_empty() {
+            let hidden: Vec<_> = match mine_probabilities(game) {
+                Some(probabilities) => probabilities
+                    .loc_iter()
+                    .filter(|(l, _)| game.fog()[*l].is_hidden())
+                    .map(|(l, &p)| (l, p))
+                    .collect(),
+                // every hidden cell is considered equally risky
+                None => game
+                    .fog()
+                    .loc_iter()
+                    .filter(|(_, s)| s.is_hidden())
+                    .map(|(l, _)| (l, 0.5))
+                    .collect(),
+            };
+            let certain = |mine: bool| {
+                hidden
+                    .iter()
+                    .filter(move |(_, p)| match mine {
+                        true => *p > 1.0 - CERTAINTY,
+                        false => *p < CERTAINTY,
+                    })
+                    .map(|&(l, _)| l)
+            };
+            commands = commands_of(certain(false), certain(true));
+
+            if commands.is_empty() {
+                let lowest_risk = hidden.iter().min_by(|(_, p), (_, q)| p.total_cmp(q));
+                match lowest_risk {
+                    Some(&(l, _)) => {
+                        guesses += 1;
+                        commands.push(PendingCommand::new(l, Action::Reveal));
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        for command in commands {
+            game.execute(command);
+            if !matches!(game.state(), GameState::InProgress { .. }) {
+                break;
+            }
+        }
+    }
+
+    GameReport {
+        won: game.state().is_win(),
+        guesses,
+        duration: start.elapsed(),
+    }
+}
+
+/// Reveals the hidden safe cells and marks the hidden mines, in a deterministic order.
+fn commands<S, M>(game: &Minefield, safe: S, mines: M) -> Vec<PendingCommand>
+where
+    S: IntoIterator<Item = Location>,
+    M: IntoIterator<Item = Location>,
+{
+    let is_hidden = |l: &Location| game.fog().get(*l).is_some_and(State::is_hidden);
+    commands_of(
+        safe.into_iter().filter(is_hidden),
+        mines.into_iter().filter(is_hidden),
+    )
+}
+
+fn commands_of<S, M>(safe: S, mines: M) -> Vec<PendingCommand>
+where
+    S: IntoIterator<Item = Location>,
+    M: IntoIterator<Item = Location>,
+{
+    let mut safe: Vec<_> = safe.into_iter().collect();
+    let mut mines: Vec<_> = mines.into_iter().collect();
+    safe.sort_unstable();
+    mines.sort_unstable();
+    let reveals = safe
+        .into_iter()
+        .map(|l| PendingCommand::new(l, Action::Reveal));
+    let marks = mines
+        .into_iter()
+        .map(|l| PendingCommand::new(l, Action::Mark));
+    reveals.chain(marks).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Area, GroundKind},
+        solver::{DpllBackend, RuleBackend},
+    };
+
+    #[test]
+    fn bot_wins_without_guessing() {
+        // The opening from the opposite corner surrounds the mine with ones.
+        let mut ground = Area::new(4, 4);
+        ground[Location::new(0_usize, 0_usize)] = GroundKind::Mine;
+        let mut game = Minefield::with_ground(ground);
+
+        let report = autoplay(&mut game, Location::new(3_usize, 3_usize), &RuleBackend);
+        assert!(report.won);
+        assert_eq!(report.guesses, 0);
+        assert!(game.fog()[Location::new(0_usize, 0_usize)].is_marked());
+    }
+
+    #[test]
+    fn bot_guesses_coin_flip() {
+        // The mine and the dirt in the top row cannot be distinguished.
+        let mut ground = Area::new(2, 3);
+        ground[Location::new(1_usize, 0_usize)] = GroundKind::Mine;
+        let mut game = Minefield::with_ground(ground);
+
+        let report = autoplay(&mut game, Location::new(0_usize, 2_usize), &DpllBackend);
+        assert_eq!(report.guesses, 1);
+        // Ties go to the first cell in reading order, which is the safe one here.
+        assert!(report.won);
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use rand::Rng;
+
+use crate::{
+    core::{Location, Minefield, MinefieldGenerator, Parameters},
+    solver::SolverBackend,
+};
+
+use super::{autoplay, GameReport};
+
+/// The board sizes of the classic difficulty levels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Preset {
+    pub fn parameters(self) -> Parameters {
+        match self {
+            Preset::Beginner => Parameters::new(9, 9, 10),
+            Preset::Intermediate => Parameters::new(16, 16, 40),
+            Preset::Expert => Parameters::new(30, 16, 99),
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Preset::Beginner => "beginner",
+            Preset::Intermediate => "intermediate",
+            Preset::Expert => "expert",
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "beginner" => Ok(Preset::Beginner),
+            "intermediate" => Ok(Preset::Intermediate),
+            "expert" => Ok(Preset::Expert),
+            _ => Err(format!("Unknown preset {:?}.", s)),
+        }
+    }
+}
+
+/// Aggregated outcomes of the games of a simulation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SimulationReport {
+    pub games: usize,
+    pub wins: usize,
+    pub guesses: usize,
+    /// Time the bot needed for all games, summed over all threads.
+    pub total_time: Duration,
+}
+
+impl SimulationReport {
+    fn add(&mut self, game: GameReport) {
+        self.games += 1;
+        self.wins += game.won as usize;
+        self.guesses += game.guesses;
+        self.total_time += game.duration;
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+
+    pub fn guesses_per_game(&self) -> f64 {
+        self.guesses as f64 / self.games.max(1) as f64
+    }
+
+    pub fn time_per_game(&self) -> Duration {
+        self.total_time / self.games.max(1) as u32
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} games, {:.1}% won, {:.2} guesses per game, {:.1} ms per game",
+            self.games,
+            self.win_rate() * 100.0,
+            self.guesses_per_game(),
+            self.time_per_game().as_secs_f64() * 1000.0
+        )
+    }
+}
+
+/// Lets the bot play `games` games on boards of `generator` with the deductions of `backend`,
+/// spread over `threads` threads.
+///
+/// The seeds of the games are derived from `params`, so the same parameters yield the same
+/// boards regardless of the generator and the number of threads. Each thread creates its own
+/// generator, the first click is always in the center of the board.
+pub fn simulate<F>(
+    params: Parameters,
+    games: usize,
+    threads: usize,
+    backend: &dyn SolverBackend,
+    generator: F,
+) -> SimulationReport
+where
+    F: Fn() -> Box<dyn MinefieldGenerator> + Sync,
+{
+    let mut rng = params.rng();
+    let seeds: Vec<u64> = (0..games).map(|_| rng.gen()).collect();
+    let click = Location::new(params.width / 2, params.height / 2);
+    let next_game = AtomicUsize::new(0);
+    let report = Mutex::new(SimulationReport::default());
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                while let Some(&seed) = seeds.get(next_game.fetch_add(1, Ordering::Relaxed)) {
+                    let mut game = Minefield::with_generator(params.with_seed(seed), generator());
+                    let game_report = autoplay(&mut game, click, backend);
+                    report
+                        .lock()
+                        .expect("Another simulation thread panicked.")
+                        .add(game_report);
+                }
+            });
+        }
+    });
+    report
+        .into_inner()
+        .expect("Another simulation thread panicked.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generator::{ImprovedGenerator, SimpleGenerator},
+        solver::RuleBackend,
+    };
+
+    #[test]
+    fn simulation_does_not_depend_on_thread_count() {
+        let params = Parameters::new(5, 5, 3).with_seed(3);
+        let single = simulate(params, 6, 1, &RuleBackend, || Box::new(SimpleGenerator));
+        let parallel = simulate(params, 6, 3, &RuleBackend, || Box::new(SimpleGenerator));
+        assert_eq!(single.games, 6);
+        assert_eq!(
+            (single.wins, single.guesses),
+            (parallel.wins, parallel.guesses)
+        );
+        assert!(single.wins > 0);
+
+        // Both uniform generators produce the same boards for the same seeds.
+        let improved = simulate(params, 6, 2, &RuleBackend, || Box::new(ImprovedGenerator));
+        assert_eq!(
+            (single.wins, single.guesses),
+            (improved.wins, improved.guesses)
+        );
+    }
+
+    #[test]
+    fn presets_round_trip() {
+        for &preset in &[Preset::Beginner, Preset::Intermediate, Preset::Expert] {
+            assert_eq!(preset.to_string().parse(), Ok(preset));
+        }
+        assert_eq!(Preset::Expert.parameters().mine_count, 99);
+        assert!("master".parse::<Preset>().is_err());
+    }
+}
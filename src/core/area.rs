@@ -45,6 +45,11 @@ impl<T> Area<T> {
             .unwrap_or(false)
     }
 
+    /// Returns `true` if the area has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.area.is_empty()
+    }
+
     /// Get a reference to the area's width.
     pub fn width(&self) -> usize {
         self.width
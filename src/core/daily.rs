@@ -0,0 +1,300 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{FirstClickPolicy, GameState, Parameters};
+
+/// A calendar date in the proleptic Gregorian calendar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Returns the current date in UTC, so everybody plays the same board at the same time.
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time is before 1970.")
+            .as_secs();
+        Self::from_days_since_epoch(seconds / 86_400)
+    }
+
+    /// Converts the number of days since 1970-01-01 to a date.
+    fn from_days_since_epoch(days: u64) -> Self {
+        // Shift the epoch to 0000-03-01, so leap days are at the end of a 400 year era.
+        let days = days + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + (month <= 2) as u64;
+        Self {
+            year: year as u32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    fn is_leap_year(year: u32) -> bool {
+        year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+    }
+
+    /// Returns the number of days of the month, `0` for months that do not exist.
+    fn days_in_month(year: u32, month: u32) -> u32 {
+        match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if Self::is_leap_year(year) => 29,
+            2 => 28,
+            _ => 0,
+        }
+    }
+
+    /// Returns the seed of the daily board.
+    pub fn seed(self) -> u64 {
+        u64::from(self.year) * 10_000 + u64::from(self.month) * 100 + u64::from(self.day)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    /// Parses dates in the format `YYYY-MM-DD`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid date {:?}, expected YYYY-MM-DD.", s);
+        let parts: Vec<_> = s.split('-').map(|p| p.parse::<u32>().ok()).collect();
+        let date = match parts.as_slice() {
+            &[Some(year), Some(month), Some(day)] => Self { year, month, day },
+            _ => return Err(invalid()),
+        };
+        match (1..=Self::days_in_month(date.year, date.month)).contains(&date.day) {
+            true => Ok(date),
+            false => Err(invalid()),
+        }
+    }
+}
+
+/// Returns the parameters of the daily board. The board is pre-opened, so the first click
+/// is derived from the date as well.
+pub fn daily_parameters(date: Date) -> Parameters {
+    Parameters::new(16, 16, 40)
+        .with_first_click(FirstClickPolicy::PreOpened)
+        .with_seed(date.seed())
+}
+
+/// The shareable outcome of a daily challenge, e.g. `2026-10-18/W/93s/120c`.
+/// Hints are appended if any were used, e.g. `2026-10-18/W/93s/120c/2h`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResultCode {
+    pub date: Date,
+    pub won: bool,
+    pub seconds: u64,
+    pub clicks: usize,
+    pub hints: usize,
+}
+
+impl ResultCode {
+    /// Returns the result of a finished game, `None` if the game is not over yet.
+    pub fn new(date: Date, state: &GameState, clicks: usize, hints: usize) -> Option<Self> {
+        let (won, duration) = match state {
+            GameState::Win { game_duration } => (true, game_duration),
+            GameState::Loss { game_duration } => (false, game_duration),
+            _ => return None,
+        };
+        Some(Self {
+            date,
+            won,
+            seconds: duration.as_secs(),
+            clicks,
+            hints,
+        })
+    }
+
+    /// Results of games played with hints are kept off the leaderboards.
+    pub fn is_ranked(&self) -> bool {
+        self.hints == 0
+    }
+
+    /// Wins beat losses, faster wins beat slower ones and fewer clicks break ties.
+    /// Losses are ranked the same way, surviving longer is not rewarded.
+    fn rank(&self, other: &Self) -> Ordering {
+        other
+            .won
+            .cmp(&self.won)
+            .then(self.seconds.cmp(&other.seconds))
+            .then(self.clicks.cmp(&other.clicks))
+    }
+
+    /// Describes how this result compares to somebody else's.
+    pub fn compare(&self, other: &Self) -> String {
+        if self.date != other.date {
+            return format!(
+                "Cannot compare, the other result is from {} instead of {}.",
+                other.date, self.date
+            );
+        }
+        let outcome = |r: &Self| match r.won {
+            true => "won",
+            false => "lost",
+        };
+        let verdict = match (self.is_ranked() && other.is_ranked(), self.rank(other)) {
+            (false, _) => "Games played with hints are not ranked.",
+            (true, Ordering::Less) => "You beat them!",
+            (true, Ordering::Equal) => "It's a draw.",
+            (true, Ordering::Greater) => "They beat you.",
+        };
+        format!(
+            "You {} in {}s with {} clicks, they {} in {}s with {} clicks. {}",
+            outcome(self),
+            self.seconds,
+            self.clicks,
+            outcome(other),
+            other.seconds,
+            other.clicks,
+            verdict
+        )
+    }
+}
+
+impl fmt::Display for ResultCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let outcome = if self.won { "W" } else { "L" };
+        write!(
+            f,
+            "{}/{}/{}s/{}c",
+            self.date, outcome, self.seconds, self.clicks
+        )?;
+        match self.hints {
+            0 => Ok(()),
+            hints => write!(f, "/{}h", hints),
+        }
+    }
+}
+
+impl FromStr for ResultCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid result code {:?}.", s);
+        let parts: Vec<_> = s.trim().split('/').collect();
+        let (date, outcome, seconds, clicks, hints) = match *parts.as_slice() {
+            [date, outcome, seconds, clicks] => (date, outcome, seconds, clicks, None),
+            [date, outcome, seconds, clicks, hints] => {
+                (date, outcome, seconds, clicks, Some(hints))
+            }
+            _ => return Err(invalid()),
+        };
+        let number = |part: &str, unit| {
+            part.strip_suffix(unit)
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(invalid)
+        };
+        Ok(Self {
+            date: date.parse()?,
+            won: match outcome {
+                "W" => true,
+                "L" => false,
+                _ => return Err(invalid()),
+            },
+            seconds: number(seconds, 's')?,
+            clicks: number(clicks, 'c')? as usize,
+            hints: hints.map_or(Ok(0), |h| number(h, 'h'))? as usize,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Minefield;
+
+    fn date(year: u32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    #[test]
+    fn days_since_epoch_are_converted_to_dates() {
+        assert_eq!(Date::from_days_since_epoch(0), date(1970, 1, 1));
+        assert_eq!(Date::from_days_since_epoch(11_016), date(2000, 2, 29));
+        assert_eq!(Date::from_days_since_epoch(20_744), date(2026, 10, 18));
+        assert_eq!("2026-10-18".parse(), Ok(date(2026, 10, 18)));
+        assert!("2026-13-01".parse::<Date>().is_err());
+        assert!("2023-04-31".parse::<Date>().is_err());
+        assert_eq!("2024-02-29".parse(), Ok(date(2024, 2, 29)));
+        assert!("2024-02-30".parse::<Date>().is_err());
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("1900-02-29".parse::<Date>().is_err());
+        assert_eq!("2000-02-29".parse(), Ok(date(2000, 2, 29)));
+        assert!("2026-10".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn daily_board_is_the_same_for_everybody() {
+        let params = daily_parameters(date(2026, 10, 18));
+        let first = Minefield::new(params);
+        let second = Minefield::new(params);
+        assert_eq!(first.fog(), second.fog());
+        assert!(first.fog().iter().any(|s| s.is_revealed()));
+
+        let other_day = Minefield::new(daily_parameters(date(2026, 10, 19)));
+        assert_ne!(first.fog(), other_day.fog());
+    }
+
+    #[test]
+    fn result_codes_round_trip_and_compare() {
+        let mine = ResultCode {
+            date: date(2026, 10, 18),
+            won: true,
+            seconds: 93,
+            clicks: 120,
+            hints: 0,
+        };
+        assert_eq!(mine.to_string(), "2026-10-18/W/93s/120c");
+        assert_eq!(mine.to_string().parse(), Ok(mine));
+        assert!("2026-10-18/X/93s/120c".parse::<ResultCode>().is_err());
+
+        let faster = ResultCode {
+            seconds: 80,
+            ..mine
+        };
+        let lost = ResultCode {
+            won: false,
+            seconds: 10,
+            ..mine
+        };
+        assert!(mine.compare(&faster).ends_with("They beat you."));
+        assert!(mine.compare(&lost).ends_with("You beat them!"));
+        assert!(mine.compare(&mine).ends_with("It's a draw."));
+        let other_day = ResultCode {
+            date: date(2026, 10, 17),
+            ..mine
+        };
+        assert!(mine.compare(&other_day).starts_with("Cannot compare"));
+
+        let hinted = ResultCode { hints: 2, ..faster };
+        assert_eq!(hinted.to_string(), "2026-10-18/W/80s/120c/2h");
+        assert_eq!(hinted.to_string().parse(), Ok(hinted));
+        assert!(!hinted.is_ranked());
+        assert!(mine.compare(&hinted).ends_with("not ranked."));
+    }
+}
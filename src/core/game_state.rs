@@ -24,11 +24,14 @@ impl GameState {
         let new = match self {
             GameState::Initial { .. } => match fog.iter().all(State::is_hidden) {
                 true => self.clone(),
-                false => match lost {
-                    true => GameState::Loss {
+                false => match (won, lost) {
+                    (_, true) => GameState::Loss {
                         game_duration: Duration::ZERO,
                     },
-                    false => GameState::InProgress {
+                    (true, false) => GameState::Win {
+                        game_duration: Duration::ZERO,
+                    },
+                    (false, false) => GameState::InProgress {
                         start_time: Instant::now(),
                     },
                 },
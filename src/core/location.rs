@@ -1,12 +1,12 @@
 use std::{
-    array::IntoIter,
     convert::TryInto,
     fmt,
     ops::{Add, AddAssign, Mul, Sub, SubAssign},
 };
 
-#[derive(Hash, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Hash, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Bounded {
+    #[default]
     Invalid,
     Valid(usize),
 }
@@ -20,12 +20,6 @@ impl fmt::Display for Bounded {
     }
 }
 
-impl Default for Bounded {
-    fn default() -> Self {
-        Self::Invalid
-    }
-}
-
 impl Bounded {
     fn op<F: Fn(usize, usize) -> Option<usize>>(self, other: Self, operation: F) -> Self {
         use Bounded::*;
@@ -147,7 +141,7 @@ impl Location {
 
     pub fn neighbours(&self) -> impl Iterator<Item = Location> {
         use Direction::*;
-        IntoIter::new([
+        IntoIterator::into_iter([
             self.mv(Up).mv(Left),
             self.mv(Up),
             self.mv(Up).mv(Right),
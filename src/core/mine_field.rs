@@ -1,12 +1,21 @@
-use std::{collections::VecDeque, convert::TryInto, fmt, time::Instant};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    convert::TryInto,
+    fmt,
+    time::Instant,
+};
 
-use crate::generator::{DummyGenerator, ImprovedGenerator};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::generator::{BoardFileGenerator, ImprovedGenerator, OnRestart};
 
 use super::{Action, Area, ExecutedCommand, GameState, Location, PendingCommand};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum GroundKind {
     Mine,
+    #[default]
     Dirt,
 }
 
@@ -22,17 +31,14 @@ impl GroundKind {
     }
 }
 
-impl Default for GroundKind {
-    fn default() -> Self {
-        Self::Dirt
-    }
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum State {
+    #[default]
     Hidden,
     Marked,
-    Revealed { adj_mines: usize },
+    Revealed {
+        adj_mines: usize,
+    },
     Exploded,
 }
 
@@ -74,12 +80,6 @@ impl State {
     }
 }
 
-impl Default for State {
-    fn default() -> Self {
-        Self::Hidden
-    }
-}
-
 impl fmt::Display for State {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -97,10 +97,52 @@ pub enum ExecutionResult {
     SuccessNoStateChange(ExecutedCommand),
 }
 
+/// Decides how the first reveal of a game is protected from hitting a mine.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FirstClickPolicy {
+    /// Mines are placed without looking at the first click, it may hit a mine.
+    NoGuarantee,
+    /// The first revealed cell is never a mine.
+    SafeCell,
+    /// The first revealed cell has no adjacent mines and therefore opens an area.
+    #[default]
+    Opening,
+    /// The game reveals an opening at a random location before the first click.
+    PreOpened,
+}
+
+impl FirstClickPolicy {
+    /// Returns the locations that must not contain a mine if the first reveal happens at `click`.
+    pub fn safe_area(self, click: Location) -> Vec<Location> {
+        match self {
+            FirstClickPolicy::NoGuarantee => vec![],
+            FirstClickPolicy::SafeCell => vec![click],
+            FirstClickPolicy::Opening | FirstClickPolicy::PreOpened => {
+                std::iter::once(click).chain(click.neighbours()).collect()
+            }
+        }
+    }
+
+    /// Returns the next less restrictive policy which is used if the board is too dense
+    /// to honour this one.
+    pub fn weaken(self) -> Self {
+        match self {
+            FirstClickPolicy::NoGuarantee | FirstClickPolicy::SafeCell => {
+                FirstClickPolicy::NoGuarantee
+            }
+            FirstClickPolicy::Opening | FirstClickPolicy::PreOpened => FirstClickPolicy::SafeCell,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub struct Parameters {
     pub width: usize,
     pub height: usize,
     pub mine_count: usize,
+    pub first_click: FirstClickPolicy,
+    /// Determines all randomness of a game, the same seed and first click yield the same board.
+    pub seed: u64,
 }
 
 impl Parameters {
@@ -109,37 +151,201 @@ impl Parameters {
             width,
             height,
             mine_count,
+            first_click: Default::default(),
+            seed: rand::random(),
         }
     }
+
+    pub fn with_first_click(mut self, first_click: FirstClickPolicy) -> Self {
+        self.first_click = first_click;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns a random number generator that is fully determined by the seed.
+    pub fn rng(&self) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(self.seed)
+    }
+}
+
+/// The random number stream used to pick the start location of pre-opened games.
+const PRE_OPEN_STREAM: u64 = 1;
+
+/// The first reveal of a game and the cells a generator must keep free of mines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExclusionZone {
+    /// The location of the first reveal.
+    pub click: Location,
+    /// Locations that must not contain a mine, locations outside of the board are ignored.
+    pub excluded: BTreeSet<Location>,
+    /// The first click policy the zone honours, `None` if it was not derived from one.
+    pub policy: Option<FirstClickPolicy>,
 }
 
+impl ExclusionZone {
+    pub fn new<I: IntoIterator<Item = Location>>(click: Location, excluded: I) -> Self {
+        Self {
+            click,
+            excluded: excluded.into_iter().collect(),
+            policy: None,
+        }
+    }
+
+    /// Excludes the cells given by the [`FirstClickPolicy`] of `params`.
+    ///
+    /// If the board is too dense to honour the policy, it is weakened until enough cells for
+    /// all mines remain. The zone's `policy` is the one that was applied.
+    pub fn for_first_click(params: &Parameters, click: Location) -> Self {
+        let mut policy = params.first_click;
+        loop {
+            let zone = Self {
+                policy: Some(policy),
+                ..Self::new(click, policy.safe_area(click))
+            };
+            let free_cells = params.width * params.height - zone.excluded_indices(params).len();
+            if policy == FirstClickPolicy::NoGuarantee || params.mine_count <= free_cells {
+                break zone;
+            }
+            policy = policy.weaken();
+        }
+    }
+
+    /// Returns the indices of all excluded locations on the board described by `params`.
+    pub fn excluded_indices(&self, params: &Parameters) -> BTreeSet<usize> {
+        let (width, height) = (params.width, params.height);
+        self.excluded
+            .iter()
+            .filter(|l| l.as_tuple().is_some_and(|(x, y)| x < width && y < height))
+            .filter_map(|l| l.to_index(width))
+            .collect()
+    }
+}
+
+/// Returned by a [`MinefieldGenerator`] that cannot generate a board.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerationError {
+    /// There are fewer cells outside of the exclusion zone than mines.
+    TooManyMines {
+        mine_count: usize,
+        free_cells: usize,
+    },
+    /// None of the boards tried for a puzzle had a unique solution.
+    NoUniquePuzzle { attempts: usize },
+    /// The solver needed to guess on every board that was tried.
+    NoGuessFree { attempts: usize },
+    /// None of the boards tried was within the requested difficulty band.
+    OutsideDifficultyBand { attempts: usize },
+}
+
+impl fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenerationError::TooManyMines {
+                mine_count,
+                free_cells,
+            } => write!(
+                f,
+                "Cannot place {} mines on {} free cells.",
+                mine_count, free_cells
+            ),
+            GenerationError::NoUniquePuzzle { attempts } => write!(
+                f,
+                "No board with a unique solution found in {} attempts.",
+                attempts
+            ),
+            GenerationError::NoGuessFree { attempts } => write!(
+                f,
+                "No board without guessing found in {} attempts.",
+                attempts
+            ),
+            GenerationError::OutsideDifficultyBand { attempts } => write!(
+                f,
+                "No board within the difficulty band found in {} attempts.",
+                attempts
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GenerationError {}
+
 pub trait MinefieldGenerator {
-    fn generate(&mut self, params: Parameters, not_a_mine: Location) -> Area<GroundKind>;
+    /// Places exactly `params.mine_count` mines outside of the exclusion zone.
+    fn generate(
+        &mut self,
+        params: Parameters,
+        zone: &ExclusionZone,
+    ) -> Result<Area<GroundKind>, GenerationError>;
+
+    /// Called before every game, generators with fixed boards use it to dictate the size and
+    /// mine count of the next game.
+    fn next_parameters(&mut self, params: Parameters) -> Parameters {
+        params
+    }
+
+    /// Returns how many boards were tried for the last board, for generators that retry until
+    /// a board qualifies.
+    fn attempts(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the difficulty score of the last board, for generators that analyze it.
+    fn score(&self) -> Option<f64> {
+        None
+    }
 }
 
 pub struct Minefield {
     ground: Area<GroundKind>,
     fog: Area<State>,
     state: GameState,
+    params: Parameters,
+    /// The first click policy the mines were placed with, see [`Minefield::first_click`].
+    first_click: FirstClickPolicy,
+    /// Why the mines of this game could not be placed, see [`Minefield::generation_error`].
+    generation_error: Option<GenerationError>,
     generator: Box<dyn MinefieldGenerator>,
 }
 
 impl Minefield {
     pub fn new(params: Parameters) -> Self {
-        Self {
+        Self::with_generator(params, Box::new(ImprovedGenerator))
+    }
+
+    pub fn with_generator(params: Parameters, mut generator: Box<dyn MinefieldGenerator>) -> Self {
+        let params = generator.next_parameters(params);
+        let mut mf = Self {
             ground: Default::default(),
             fog: Area::new(params.width, params.height),
             state: GameState::new(params.mine_count),
-            generator: Box::new(ImprovedGenerator),
-        }
+            params,
+            first_click: params.first_click,
+            generation_error: None,
+            generator,
+        };
+        mf.pre_open();
+        mf
     }
 
-    pub fn with_generator(params: Parameters, generator: Box<dyn MinefieldGenerator>) -> Self {
+    /// Creates a game that has not been started yet on top of the given ground.
+    pub fn with_ground(ground: Area<GroundKind>) -> Self {
+        let mine_count = ground.iter().filter(|g| g.is_mine()).count();
+        let params = Parameters::new(ground.width(), ground.height(), mine_count);
         Self {
-            ground: Default::default(),
+            generator: Box::new(BoardFileGenerator::new(
+                vec![ground.clone()],
+                OnRestart::Replay,
+            )),
             fog: Area::new(params.width, params.height),
-            state: GameState::new(params.mine_count),
-            generator,
+            ground,
+            state: GameState::new(mine_count),
+            first_click: params.first_click,
+            generation_error: None,
+            params,
         }
     }
 
@@ -189,13 +395,20 @@ impl Minefield {
             })
             .collect();
 
+        let mine_count = ground.iter().filter(|g| g.is_mine()).count();
         Self {
+            generator: Box::new(BoardFileGenerator::new(
+                vec![ground.clone()],
+                OnRestart::Replay,
+            )),
             ground,
             fog: Area::with_area(width, height, fog),
             state: GameState::InProgress {
                 start_time: Instant::now(),
             },
-            generator: Box::new(DummyGenerator),
+            params: Parameters::new(width, height, mine_count),
+            first_click: Default::default(),
+            generation_error: None,
         }
     }
 
@@ -207,6 +420,34 @@ impl Minefield {
         &self.state
     }
 
+    pub fn params(&self) -> &Parameters {
+        &self.params
+    }
+
+    /// Returns the first click policy the mines were placed with. It is weaker than the one of
+    /// the parameters if the board is too dense to honour that, see
+    /// [`ExclusionZone::for_first_click`].
+    pub fn first_click(&self) -> FirstClickPolicy {
+        self.first_click
+    }
+
+    /// Returns why the generator failed to place the mines of this game, the game cannot be
+    /// started then.
+    pub fn generation_error(&self) -> Option<&GenerationError> {
+        self.generation_error.as_ref()
+    }
+
+    /// Returns how many boards the generator tried for this game, see
+    /// [`MinefieldGenerator::attempts`].
+    pub fn generator_attempts(&self) -> Option<usize> {
+        self.generator.attempts()
+    }
+
+    /// Returns the difficulty score of this game's board, see [`MinefieldGenerator::score`].
+    pub fn generator_score(&self) -> Option<f64> {
+        self.generator.score()
+    }
+
     pub fn width(&self) -> usize {
         self.fog.width()
     }
@@ -216,7 +457,10 @@ impl Minefield {
     }
 
     pub fn mine_count(&self) -> usize {
-        self.ground.iter().filter(|g| g.is_mine()).count()
+        match self.ground.is_empty() {
+            true => self.params.mine_count,
+            false => self.ground.iter().filter(|g| g.is_mine()).count(),
+        }
     }
 
     pub fn unobserved_count(&self) -> usize {
@@ -239,11 +483,48 @@ impl Minefield {
         self.fog.iter().filter(|g| g.is_marked()).count()
     }
 
+    /// Restarts the game with a fresh seed.
     pub fn reset(&mut self) {
-        let (width, height, mine_count) = (self.width(), self.height(), self.mine_count());
+        self.params.seed = rand::random();
+        self.params = self.generator.next_parameters(self.params);
+        let Parameters {
+            width,
+            height,
+            mine_count,
+            ..
+        } = self.params;
         self.ground = Default::default();
         self.fog = Area::new(width, height);
         self.state = GameState::new(mine_count);
+        self.first_click = self.params.first_click;
+        self.generation_error = None;
+        self.pre_open();
+    }
+
+    /// Generates the mines and reveals an opening at a random location
+    /// if the game is configured with [`FirstClickPolicy::PreOpened`].
+    fn pre_open(&mut self) {
+        if self.params.first_click != FirstClickPolicy::PreOpened {
+            return;
+        }
+        let cell_count = self.width() * self.height();
+        if cell_count == 0 {
+            return;
+        }
+        // Use a separate stream so the start location does not correlate with the mines.
+        let mut rng = self.params.rng();
+        rng.set_stream(PRE_OPEN_STREAM);
+        let index = rng.gen_range(0..cell_count);
+        let start = Location::from_index(index, self.width());
+        let zone = ExclusionZone::for_first_click(&self.params, start);
+        match self.generator.generate(self.params, &zone) {
+            Ok(ground) => {
+                self.ground = ground;
+                self.first_click = zone.policy.unwrap_or(self.first_click);
+                Self::reveal_location(&mut self.fog, &self.ground, start);
+            }
+            Err(e) => self.generation_error = Some(e),
+        }
     }
 
     pub fn reveal_all(&mut self) {
@@ -312,20 +593,35 @@ impl Minefield {
     }
 
     pub fn execute(&mut self, cmd: PendingCommand) -> ExecutionResult {
-        eprintln!(
-            "Executing action {:?} at location {}",
-            cmd.action, cmd.location
-        );
         let Minefield {
             ground,
             fog,
             state,
+            params,
+            first_click,
+            generation_error,
             generator,
         } = self;
 
-        if let GameState::Initial { mine_count } = *state {
-            let params = Parameters::new(fog.width(), fog.height(), mine_count);
-            *ground = generator.generate(params, cmd.location);
+        // Only the first reveal places the mines, marks before it leave the ground untouched.
+        let is_first_reveal = ground.is_empty()
+            && matches!(
+                (cmd.action, fog.get(cmd.location)),
+                (Action::Reveal, Some(State::Hidden))
+            );
+        if is_first_reveal {
+            let zone = ExclusionZone::for_first_click(params, cmd.location);
+            match generator.generate(*params, &zone) {
+                Ok(generated) => {
+                    *ground = generated;
+                    *first_click = zone.policy.unwrap_or(*first_click);
+                    *generation_error = None;
+                }
+                Err(e) => {
+                    *generation_error = Some(e);
+                    return ExecutionResult::Failed;
+                }
+            }
         }
 
         let mut updated_locations = vec![cmd.location];
@@ -338,7 +634,15 @@ impl Minefield {
             _ => return ExecutionResult::Failed,
         }
 
-        match state.update(fog, ground) {
+        let state_changed = match (&state, cmd.action) {
+            // Marks cannot start or decide a game that has not been revealed yet.
+            (GameState::Initial { .. }, Action::Mark | Action::Unmark | Action::ToggleMark) => {
+                false
+            }
+            _ => state.update(fog, ground),
+        };
+
+        match state_changed {
             true => ExecutionResult::SuccessAndStateChange(cmd.executed(updated_locations)),
             false => ExecutionResult::SuccessNoStateChange(cmd.executed(updated_locations)),
         }
@@ -420,4 +724,66 @@ mod tests {
             check(symbol, State::Revealed { adj_mines: 0 }, GroundKind::Dirt);
         }
     }
+
+    #[test]
+    fn mark_before_first_reveal_keeps_game_initial() {
+        let mut mf = Minefield::new(Parameters::new(5, 5, 5));
+        let marked = Location::new(4_usize, 4_usize);
+
+        mf.execute(PendingCommand::new(marked, Action::Mark));
+        assert!(mf.ground.is_empty());
+        assert!(matches!(mf.state, GameState::Initial { .. }));
+        assert_eq!(mf.mine_count(), 5);
+
+        mf.execute(PendingCommand::new((0, 0), Action::Reveal));
+        assert!(!mf.ground.is_empty());
+        assert!(!matches!(mf.state, GameState::Initial { .. }));
+        assert_eq!(mf.fog[marked], State::Marked);
+        assert_eq!(
+            mf.fog[Location::new(0_usize, 0_usize)],
+            State::Revealed { adj_mines: 0 }
+        );
+    }
+
+    #[test]
+    fn dense_board_reports_weakened_first_click_policy() {
+        let mut mf = Minefield::new(Parameters::new(4, 4, 12));
+        assert_eq!(mf.first_click(), FirstClickPolicy::Opening);
+
+        mf.execute(PendingCommand::new((1, 1), Action::Reveal));
+        assert_eq!(mf.first_click(), FirstClickPolicy::SafeCell);
+        assert!(mf.fog[Location::new(1_usize, 1_usize)].is_revealed());
+
+        mf.reset();
+        assert_eq!(mf.first_click(), FirstClickPolicy::Opening);
+    }
+
+    #[test]
+    fn pre_opened_game_reveals_opening() {
+        let params = Parameters::new(10, 10, 10).with_first_click(FirstClickPolicy::PreOpened);
+        let mut mf = Minefield::new(params);
+        assert!(matches!(mf.state, GameState::Initial { .. }));
+        assert!(mf
+            .fog
+            .iter()
+            .any(|&s| s == State::Revealed { adj_mines: 0 }));
+
+        mf.reset();
+        assert!(matches!(mf.state, GameState::Initial { .. }));
+        assert!(mf
+            .fog
+            .iter()
+            .any(|&s| s == State::Revealed { adj_mines: 0 }));
+    }
+
+    #[test]
+    fn pre_opened_game_is_reproducible_from_seed() {
+        let params = Parameters::new(16, 16, 40)
+            .with_first_click(FirstClickPolicy::PreOpened)
+            .with_seed(7);
+        let first = Minefield::new(params);
+        let second = Minefield::new(params);
+        assert_eq!(first.fog, second.fog);
+        assert_eq!(first.ground, second.ground);
+    }
 }
@@ -1,11 +1,13 @@
 mod area;
 mod command;
+mod daily;
 mod game_state;
 mod location;
 mod mine_field;
 
 pub use area::*;
 pub use command::*;
+pub use daily::*;
 pub use game_state::*;
 pub use location::*;
 pub use mine_field::*;
@@ -1,5 +1,9 @@
-use crate::core::{
-    Action, ExecutionResult, GameState, Location, Minefield, Parameters, PendingCommand, State,
+use crate::{
+    core::{
+        Action, Date, ExecutionResult, GameState, Location, Minefield, Parameters, PendingCommand,
+        ResultCode, State,
+    },
+    solver::{mine_probabilities, SolverSession},
 };
 
 use std::{
@@ -40,16 +44,30 @@ const NEW_LINE: &[u8] = "\n\r".as_bytes();
 enum InputEvent {
     Quit,
     Restart,
+    Hint,
+    Explain,
     GameAction(Action, Location),
 }
 
+/// A daily challenge and the result somebody else shared for it.
+struct Daily {
+    date: Date,
+    other: Option<ResultCode>,
+}
+
 pub struct Term {
     io: TermIo,
     mine_field: Minefield,
+    clicks: usize,
+    hints: usize,
+    daily: Option<Daily>,
+    /// Keeps the solver's facts between hints.
+    solver: SolverSession,
 }
 
 impl Term {
-    pub fn new<T, U, V>((width, height): (T, U), mines: V) -> Self
+    /// Builds the game parameters, a missing size is derived from the terminal size.
+    pub fn parameters<T, U, V>((width, height): (T, U), mines: V) -> Parameters
     where
         T: Into<Option<usize>>,
         U: Into<Option<usize>>,
@@ -61,22 +79,49 @@ impl Term {
         let size = width.into().zip(height.into());
         let (width, height) = size.or(termsize).unwrap_or((70, 40));
         let mines = mines.into().unwrap_or(width * height / 6);
+        Parameters::new(width, height, mines)
+    }
 
-        Self {
-            io: TermIo::new(width, height),
-            mine_field: Minefield::new(Parameters::new(width, height, mines)),
-        }
+    pub fn new(mine_field: Minefield) -> Self {
+        let mut term = Self {
+            io: TermIo::new(mine_field.width(), mine_field.height()),
+            mine_field,
+            clicks: 0,
+            hints: 0,
+            daily: None,
+            solver: SolverSession::new(),
+        };
+        term.redraw_all();
+        term
+    }
+
+    /// Plays the daily challenge of `date`, restarting is disabled so the first attempt counts.
+    /// The result is compared with `other` once the game is over.
+    pub fn with_daily(mut self, date: Date, other: Option<ResultCode>) -> Self {
+        self.daily = Some(Daily { date, other });
+        self
+    }
+
+    /// Returns the shareable result of a finished daily challenge.
+    pub fn daily_result(&self) -> Option<ResultCode> {
+        let daily = self.daily.as_ref()?;
+        ResultCode::new(daily.date, self.mine_field.state(), self.clicks, self.hints)
     }
 
     pub fn go(&mut self) {
-        eprintln!("start");
         while self.run() {
             std::thread::sleep(std::time::Duration::from_millis(10));
         }
     }
 
     pub fn run(&mut self) -> bool {
-        self.io.print_info(&self.mine_field);
+        self.io.print_info(&self.mine_field, self.hints);
+        if let Some(result) = self.daily_result() {
+            let comparison = self.daily.as_ref().and_then(|d| d.other);
+            let comparison = comparison.map(|other| result.compare(&other));
+            let message = format!("Result: {} {}", result, comparison.unwrap_or_default());
+            self.io.print_message(&message);
+        }
         match self.mine_field.state() {
             GameState::Initial { .. } => self.run_initial(),
             GameState::InProgress { .. } => self.run_in_progress(),
@@ -90,6 +135,15 @@ impl Term {
                 self.execute_action(l, action);
                 true
             }
+            // pre-opened boards already show an opening before the first click
+            Some(InputEvent::Hint) => {
+                self.hint();
+                true
+            }
+            Some(InputEvent::Explain) => {
+                self.explain();
+                true
+            }
             Some(InputEvent::Quit) => false,
             _ => true,
         }
@@ -98,9 +152,17 @@ impl Term {
     pub fn run_after(&mut self) -> bool {
         match self.io.read_input() {
             Some(InputEvent::Quit) => false,
-            Some(InputEvent::Restart) => {
+            Some(InputEvent::Restart) if self.daily.is_none() => {
+                self.clicks = 0;
+                self.hints = 0;
+                self.io.highlighted = None;
+                self.solver = SolverSession::new();
                 self.mine_field.reset();
+                // The next board may have a different size, e.g. in a playlist of board files.
+                self.io.width = self.mine_field.width();
+                self.io.height = self.mine_field.height();
                 self.io.reset();
+                self.redraw_all();
                 true
             }
             _ => true,
@@ -113,12 +175,89 @@ impl Term {
                 self.execute_action(l, action);
                 true
             }
+            Some(InputEvent::Hint) => {
+                self.hint();
+                true
+            }
+            Some(InputEvent::Explain) => {
+                self.explain();
+                true
+            }
             Some(InputEvent::Quit) => false,
             _ => true,
         }
     }
 
+    /// Highlights a cell the solver can deduce. If there is none, the hidden cell that is
+    /// least likely to be a mine is highlighted instead.
+    fn hint(&mut self) {
+        let fog = self.mine_field.fog();
+        if fog.iter().all(State::is_hidden) {
+            self.io
+                .print_message("No hint is available before the first cell is revealed.");
+            return;
+        }
+        let is_hidden = |l: &Location| fog.get(*l).is_some_and(State::is_hidden);
+        let analysis = self.solver.update(&self.mine_field);
+        let safe = analysis.safe.into_iter().filter(is_hidden).min();
+        let mine = analysis.mines.into_iter().filter(is_hidden).min();
+        let (location, message) = match (safe, mine) {
+            (Some(l), _) => (l, format!("Hint: {} is safe.", l)),
+            (None, Some(l)) => (l, format!("Hint: {} is a mine.", l)),
+            (None, None) => {
+                let probabilities = match mine_probabilities(&self.mine_field) {
+                    Some(probabilities) => probabilities,
+                    None => {
+                        self.io.print_message(
+                            "A guess is required, but the position is too large to compute the mine probabilities.",
+                        );
+                        return;
+                    }
+                };
+                let lowest_risk = probabilities
+                    .loc_iter()
+                    .filter(|(l, _)| is_hidden(l))
+                    .min_by(|(_, p), (_, q)| p.total_cmp(q));
+                let (l, p) = match lowest_risk {
+                    Some(lowest_risk) => lowest_risk,
+                    None => return,
+                };
+                let message = format!(
+                    "A guess is required, {} has the lowest risk of {:.0}%.",
+                    l,
+                    p * 100.0
+                );
+                (l, message)
+            }
+        };
+        self.hints += 1;
+        self.highlight(Some(location));
+        self.io.print_message(&message);
+    }
+
+    /// Prints how the solver deduced the highlighted cell.
+    fn explain(&mut self) {
+        let lines = match self.io.highlighted {
+            Some(l) => explanation(&self.mine_field, l),
+            None => vec!["Press h for a hint first.".into()],
+        };
+        self.io.print_lines(&lines);
+    }
+
+    /// Moves the highlight to `location`, `None` removes it.
+    fn highlight(&mut self, location: Option<Location>) {
+        let previous = std::mem::replace(&mut self.io.highlighted, location);
+        self.redraw(previous.into_iter().chain(location));
+    }
+
     fn execute_action(&mut self, l: Location, action: Action) {
+        if self.io.highlighted.is_some() {
+            self.highlight(None);
+            self.io.print_message("");
+        }
+        if self.lookup(l).is_some() {
+            self.clicks += 1;
+        }
         let commands = match self.lookup(l).map(State::is_revealed) {
             Some(true) => self.reveal_neighbours(l),
             Some(false) => vec![PendingCommand::new(l, action)],
@@ -130,7 +269,6 @@ impl Term {
                 use ExecutionResult::*;
                 match self.mine_field.execute(pending) {
                     SuccessAndStateChange(done) | SuccessNoStateChange(done) => {
-                        eprintln!("Applied action.");
                         done.updated_locations
                     }
                     Failed => vec![],
@@ -140,10 +278,7 @@ impl Term {
 
         if self.mine_field.state().is_loss() {
             self.mine_field.reveal_all();
-            self.redraw(Location::generate_all(
-                self.mine_field.width(),
-                self.mine_field.height(),
-            ));
+            self.redraw_all();
         } else {
             self.redraw(affected_locations);
         }
@@ -154,7 +289,6 @@ impl Term {
     }
 
     fn reveal_neighbours(&self, l: Location) -> Vec<PendingCommand> {
-        eprintln!("Trying to reveal all neighbours.");
         let expected = match self.lookup(l) {
             Some(&State::Revealed { adj_mines }) => adj_mines,
             _ => return vec![],
@@ -164,14 +298,9 @@ impl Term {
             .filter_map(|l| self.lookup(l))
             .filter(|s| s.is_marked())
             .count();
-
-        eprintln!("Expected: {}, Actual: {}", expected, actual);
-
         if expected != actual {
-            eprintln!("Not all mines marked.");
             return vec![];
         }
-        eprintln!("Trying to reveal all neighbours.");
 
         l.neighbours()
             .filter(|&l| self.lookup(l).map(State::is_hidden).unwrap_or(false))
@@ -179,6 +308,13 @@ impl Term {
             .collect()
     }
 
+    fn redraw_all(&mut self) {
+        self.redraw(Location::generate_all(
+            self.mine_field.width(),
+            self.mine_field.height(),
+        ));
+    }
+
     fn redraw<I: IntoIterator<Item = Location>>(&mut self, locations: I) {
         let Self { io, mine_field, .. } = self;
         let location_states = locations
@@ -188,11 +324,19 @@ impl Term {
     }
 }
 
+/// Returns the steps that show why the solver knows the content of `l`.
+fn explanation(mf: &Minefield, l: Location) -> Vec<String> {
+    crate::solver::explain(mf, l)
+        .unwrap_or_else(|| vec![format!("{} cannot be deduced, a guess is required.", l)])
+}
+
 struct TermIo {
     stdin: Option<AsyncReader>,
     stdout: MouseTerminal<RawTerminal<Stdout>>,
     width: usize,
     height: usize,
+    /// The cell drawn in inverted colors, e.g. to show a hint.
+    highlighted: Option<Location>,
 }
 
 impl TermIo {
@@ -202,6 +346,7 @@ impl TermIo {
             stdout: std::io::stdout().into_raw_mode().unwrap().into(),
             width,
             height,
+            highlighted: None,
         };
         s.reset();
         s
@@ -250,6 +395,8 @@ impl TermIo {
             Mouse(Press(Left, x, y)) => game_action(Action::Reveal, x, y),
             Key(Char('q')) => Some(InputEvent::Quit),
             Key(Char('r')) => Some(InputEvent::Restart),
+            Key(Char('h')) => Some(InputEvent::Hint),
+            Key(Char('e')) => Some(InputEvent::Explain),
             _ => None,
         }
     }
@@ -289,7 +436,14 @@ impl TermIo {
             .into(),
         };
 
-        self.write(&element);
+        match self.highlighted == Some(location) {
+            true => {
+                write!(self.stdout, "{}", style::Invert).unwrap();
+                self.write(&element);
+                write!(self.stdout, "{}", style::Reset).unwrap();
+            }
+            false => self.write(&element),
+        }
     }
 
     fn location_to_cursor(&self, location: Location) -> Option<cursor::Goto> {
@@ -300,27 +454,78 @@ impl TermIo {
             .map(|(x, y)| cursor::Goto(x as u16 + 2, y as u16 + 2))
     }
 
-    pub fn print_info(&mut self, mf: &Minefield) {
+    pub fn print_info(&mut self, mf: &Minefield, hints: usize) {
         let total_mines = mf.mine_count();
         let marked_mines = mf.mark_count();
         use GameState::*;
         let status: Cow<_> = match mf.state() {
-            Initial { .. } => "Ready to go.".into(),
+            Initial { .. } => match mf.generation_error() {
+                Some(e) => e.to_string().into(),
+                None => "Ready to go.".into(),
+            },
             Win { game_duration } => format!("VICTORY! ({} sec)", game_duration.as_secs()).into(),
             Loss { game_duration } => format!("DEFEAT. ({} secs)", game_duration.as_secs()).into(),
             InProgress { start_time } => {
                 format!("Time: {} seconds", start_time.elapsed().as_secs()).into()
             }
         };
+        let seed = mf.params().seed;
+        let attempts: Cow<_> = match mf.generator_attempts() {
+            Some(attempts) if attempts > 0 => format!(", Attempts: {}", attempts).into(),
+            _ => "".into(),
+        };
+        let score: Cow<_> = match mf.generator_score() {
+            Some(score) => format!(", Score: {}", score).into(),
+            None => "".into(),
+        };
+        // dense boards cannot honour every policy
+        let first_click: Cow<_> = match mf.first_click() {
+            applied if applied == mf.params().first_click => "".into(),
+            applied => format!(", First click: {:?}", applied).into(),
+        };
+        let hints: Cow<_> = match hints {
+            0 => "".into(),
+            hints => format!(", Hints: {}", hints).into(),
+        };
         let goto = cursor::Goto(3, self.height as u16 + 3);
         write!(
             self.stdout,
-            "{}Mines: {:>3}/{:>3}, {}",
-            goto, marked_mines, total_mines, status
+            "{}Mines: {:>3}/{:>3}, Seed: {}{}{}{}, {}{}{}",
+            goto,
+            marked_mines,
+            total_mines,
+            seed,
+            attempts,
+            score,
+            first_click,
+            status,
+            hints,
+            clear::UntilNewline
         )
         .unwrap();
     }
 
+    /// Prints a message in the line below the game info.
+    pub fn print_message(&mut self, message: &str) {
+        self.print_lines(&[message]);
+    }
+
+    /// Prints the lines below the game info and clears everything after them.
+    pub fn print_lines<S: AsRef<str>>(&mut self, lines: &[S]) {
+        for (i, line) in lines.iter().enumerate() {
+            let goto = cursor::Goto(3, (self.height + 4 + i) as u16);
+            write!(
+                self.stdout,
+                "{}{}{}",
+                goto,
+                line.as_ref(),
+                clear::UntilNewline
+            )
+            .unwrap();
+        }
+        write!(self.stdout, "{}", clear::AfterCursor).unwrap();
+    }
+
     fn write(&mut self, data: &[u8]) {
         self.stdout.write_all(data).unwrap();
     }
@@ -343,11 +548,11 @@ impl TermIo {
         .unwrap();
 
         use iter::once;
-        let cycle_n = |iter, n| iter::repeat(iter).take(n).flatten();
+        let cycle_n = |iter, n| iter::repeat_n(iter, n).flatten();
         // generate a single row of the mine field
         let row = |left, middle, right| {
             once(left)
-                .chain(iter::repeat(middle).take(self.width))
+                .chain(iter::repeat_n(middle, self.width))
                 .chain(once(right))
         };
 
@@ -0,0 +1,203 @@
+use std::{fs, io, path::Path};
+
+use crate::core::*;
+
+/// Decides which board is played after a restart.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnRestart {
+    /// Play the same board again.
+    Replay,
+    /// Move on to the next board and start over after the last one.
+    #[default]
+    Next,
+}
+
+/// Serves fixed mine layouts instead of placing mines randomly.
+///
+/// The size and mine count of each game are taken from the board, the exclusion zone is ignored.
+pub struct BoardFileGenerator {
+    boards: Vec<Area<GroundKind>>,
+    on_restart: OnRestart,
+    current: Option<usize>,
+}
+
+impl BoardFileGenerator {
+    /// # Panics
+    /// Panics if `boards` is empty.
+    pub fn new(boards: Vec<Area<GroundKind>>, on_restart: OnRestart) -> Self {
+        assert!(!boards.is_empty(), "At least one board is required.");
+        Self {
+            boards,
+            on_restart,
+            current: None,
+        }
+    }
+
+    /// Loads the boards of all files in the given order.
+    ///
+    /// A file contains one or more boards separated by empty lines. Boards use the grid format of
+    /// [`Minefield::new_active_game`], only the mine positions are kept.
+    pub fn load<P: AsRef<Path>>(paths: &[P], on_restart: OnRestart) -> io::Result<Self> {
+        let mut boards = vec![];
+        for path in paths {
+            let path = path.as_ref();
+            let invalid = |e| {
+                let msg = format!("Invalid board in {}: {}", path.display(), e);
+                io::Error::new(io::ErrorKind::InvalidData, msg)
+            };
+            let content = fs::read_to_string(path).map_err(|e| {
+                io::Error::new(e.kind(), format!("Cannot read {}: {}", path.display(), e))
+            })?;
+            for grid in split_boards(&content) {
+                boards.push(parse_board(&grid).map_err(invalid)?);
+            }
+        }
+        if boards.is_empty() {
+            let msg = "No boards found in the given files.";
+            return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+        }
+        Ok(Self::new(boards, on_restart))
+    }
+
+    /// Returns the index of the board that is currently played.
+    pub fn current(&self) -> usize {
+        self.current.unwrap_or(0)
+    }
+
+    pub fn board_count(&self) -> usize {
+        self.boards.len()
+    }
+}
+
+impl MinefieldGenerator for BoardFileGenerator {
+    fn generate(
+        &mut self,
+        _params: Parameters,
+        _zone: &ExclusionZone,
+    ) -> Result<Area<GroundKind>, GenerationError> {
+        Ok(self.boards[self.current()].clone())
+    }
+
+    fn next_parameters(&mut self, params: Parameters) -> Parameters {
+        let next = match (self.current, self.on_restart) {
+            (None, _) => 0,
+            (Some(current), OnRestart::Replay) => current,
+            (Some(current), OnRestart::Next) => (current + 1) % self.boards.len(),
+        };
+        self.current = Some(next);
+
+        let board = &self.boards[next];
+        Parameters {
+            width: board.width(),
+            height: board.height(),
+            mine_count: board.iter().filter(|g| g.is_mine()).count(),
+            ..params
+        }
+    }
+}
+
+/// Splits a file into the grids of its boards, boards are separated by empty lines.
+fn split_boards(content: &str) -> Vec<String> {
+    let lines: Vec<_> = content.lines().map(str::trim).collect();
+    lines
+        .split(|l| l.is_empty())
+        .filter(|rows| !rows.is_empty())
+        .map(|rows| rows.join("\n"))
+        .collect()
+}
+
+/// Parses the mine positions of a grid in the format of [`Minefield::new_active_game`].
+pub fn parse_board(grid: &str) -> Result<Area<GroundKind>, String> {
+    let rows: Vec<_> = grid.lines().map(str::trim).collect();
+    let width = rows.first().map_or(0, |r| r.chars().count());
+    if width == 0 {
+        return Err("Board is empty.".into());
+    }
+
+    let mut ground = Vec::with_capacity(width * rows.len());
+    for (y, row) in rows.iter().enumerate() {
+        if row.chars().count() != width {
+            return Err(format!("Row {} does not have {} cells.", y, width));
+        }
+        for c in row.chars() {
+            ground.push(match c {
+                c if "mMF".contains(c) => GroundKind::Mine,
+                c if "efE012345678".contains(c) => GroundKind::Dirt,
+                c => return Err(format!("Invalid character {:?} in row {}.", c, y)),
+            });
+        }
+    }
+    Ok(Area::with_area(width, rows.len(), ground))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYLIST: &str = "me
+                            ee
+
+                            eee
+                            emm
+";
+
+    fn playlist(on_restart: OnRestart) -> Minefield {
+        let boards = split_boards(PLAYLIST)
+            .iter()
+            .map(|grid| parse_board(grid).unwrap())
+            .collect();
+        let generator = BoardFileGenerator::new(boards, on_restart);
+        Minefield::with_generator(Parameters::new(9, 9, 10), Box::new(generator))
+    }
+
+    fn play(mf: &mut Minefield) -> Vec<Location> {
+        mf.execute(PendingCommand::new(
+            Location::new(0_usize, 1_usize),
+            Action::Reveal,
+        ));
+        mf.reveal_all();
+        mf.fog()
+            .loc_iter()
+            .filter(|(_, s)| s.is_exploded())
+            .map(|(l, _)| l)
+            .collect()
+    }
+
+    #[test]
+    fn parse_board_rejects_invalid_grids() {
+        assert!(parse_board("").is_err());
+        assert!(parse_board("me\ne").is_err());
+        assert!(parse_board("mx").is_err());
+
+        let board = parse_board("mE\n1F").unwrap();
+        assert_eq!((board.width(), board.height()), (2, 2));
+        assert_eq!(board.iter().filter(|g| g.is_mine()).count(), 2);
+    }
+
+    #[test]
+    fn restart_advances_playlist() {
+        let mut mf = playlist(OnRestart::Next);
+        assert_eq!((mf.width(), mf.height(), mf.mine_count()), (2, 2, 1));
+        assert_eq!(play(&mut mf), vec![Location::new(0_usize, 0_usize)]);
+
+        mf.reset();
+        assert_eq!((mf.width(), mf.height(), mf.mine_count()), (3, 2, 2));
+        let mines = vec![
+            Location::new(1_usize, 1_usize),
+            Location::new(2_usize, 1_usize),
+        ];
+        assert_eq!(play(&mut mf), mines);
+
+        mf.reset();
+        assert_eq!((mf.width(), mf.height()), (2, 2));
+    }
+
+    #[test]
+    fn restart_replays_board() {
+        let mut mf = playlist(OnRestart::Replay);
+        assert_eq!(play(&mut mf), vec![Location::new(0_usize, 0_usize)]);
+
+        mf.reset();
+        assert_eq!(play(&mut mf), vec![Location::new(0_usize, 0_usize)]);
+    }
+}
@@ -0,0 +1,289 @@
+use std::{collections::VecDeque, ops::RangeInclusive};
+
+use rand::Rng;
+
+use crate::{
+    core::*,
+    solver::{Rule, Solver},
+};
+
+use super::{play_deductions, play_without_guessing};
+
+/// Returns the 3BV of the board, the minimum number of clicks needed to clear it.
+///
+/// Every opening takes one click, as does every numbered cell that is not on the border of an opening.
+pub fn three_bv(ground: &Area<GroundKind>) -> usize {
+    let is_zero = |l: Location| {
+        ground.get(l).is_some_and(GroundKind::is_dirt)
+            && l.neighbours()
+                .all(|n| ground.get(n).is_none_or(GroundKind::is_dirt))
+    };
+
+    let mut revealed = Area::<bool>::new(ground.width(), ground.height());
+    let mut clicks = 0;
+    for (start, _) in ground.loc_iter().filter(|&(l, _)| is_zero(l)) {
+        if revealed[start] {
+            continue;
+        }
+        clicks += 1;
+        let mut pending: VecDeque<_> = std::iter::once(start).collect();
+        while let Some(current) = pending.pop_front() {
+            match revealed.get_mut(current) {
+                Some(r @ false) => *r = true,
+                _ => continue,
+            }
+            if is_zero(current) {
+                pending.extend(current.neighbours());
+            }
+        }
+    }
+
+    let isolated_numbers = ground
+        .loc_iter()
+        .filter(|&(l, g)| g.is_dirt() && !revealed[l])
+        .count();
+    clicks + isolated_numbers
+}
+
+/// Describes how hard a board is for a player who never guesses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difficulty {
+    /// Minimum number of clicks needed to clear the board.
+    pub three_bv: usize,
+    /// Whether the [`Solver`] clears the board from the first click without guessing.
+    pub solvable: bool,
+    /// Names of the solver rules without which the board can no longer be cleared without guessing.
+    pub required_rules: Vec<&'static str>,
+    /// The 3BV weighted by the required reasoning: each required rule adds half of the 3BV
+    /// and boards that require guessing count double.
+    pub score: f64,
+}
+
+impl Difficulty {
+    pub fn analyze(ground: &Area<GroundKind>, start: Location) -> Self {
+        let three_bv = three_bv(ground);
+        let solvable = play_without_guessing(ground.clone(), start)
+            .state()
+            .is_win();
+
+        let required_rules: Vec<_> = match solvable {
+            true => Solver::default_rules()
+                .iter()
+                .map(|r| r.name())
+                .filter(|&name| {
+                    let solve = |mf: &Minefield| {
+                        let rules = Solver::default_rules()
+                            .into_iter()
+                            .filter(|r| r.name() != name)
+                            .collect();
+                        Solver::solve_with_rules(mf, rules)
+                    };
+                    !play_deductions(ground.clone(), start, solve)
+                        .state()
+                        .is_win()
+                })
+                .collect(),
+            false => vec![],
+        };
+
+        let reasoning = 1.0 + required_rules.len() as f64 / 2.0;
+        let guessing = if solvable { 1.0 } else { 2.0 };
+        Self {
+            three_bv,
+            solvable,
+            required_rules,
+            score: three_bv as f64 * reasoning * guessing,
+        }
+    }
+}
+
+/// The range of difficulties a [`DifficultyGenerator`] accepts.
+#[derive(Debug, Clone)]
+pub struct DifficultyBand {
+    pub three_bv: RangeInclusive<usize>,
+    pub required_rules: Vec<&'static str>,
+}
+
+impl DifficultyBand {
+    pub fn new(three_bv: RangeInclusive<usize>) -> Self {
+        Self {
+            three_bv,
+            required_rules: vec![],
+        }
+    }
+
+    /// Only accept boards that cannot be cleared without guessing if `rule` is missing.
+    pub fn requiring(mut self, rule: &dyn Rule) -> Self {
+        self.required_rules.push(rule.name());
+        self
+    }
+
+    pub fn contains(&self, difficulty: &Difficulty) -> bool {
+        self.three_bv.contains(&difficulty.three_bv)
+            && self
+                .required_rules
+                .iter()
+                .all(|r| difficulty.required_rules.contains(r))
+    }
+}
+
+/// Wraps another generator and retries until the board falls into the given [`DifficultyBand`].
+///
+/// If no board qualifies within the maximum number of attempts,
+/// [`GenerationError::OutsideDifficultyBand`] is returned.
+pub struct DifficultyGenerator {
+    inner: Box<dyn MinefieldGenerator>,
+    band: DifficultyBand,
+    max_attempts: usize,
+    attempts: usize,
+    difficulty: Option<Difficulty>,
+}
+
+impl DifficultyGenerator {
+    pub fn new(inner: Box<dyn MinefieldGenerator>, band: DifficultyBand) -> Self {
+        Self {
+            inner,
+            band,
+            max_attempts: 10_000,
+            attempts: 0,
+            difficulty: None,
+        }
+    }
+
+    /// Limits the number of generated boards, after which generating fails.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Returns the difficulty of the last generated board, `None` if generating failed.
+    pub fn difficulty(&self) -> Option<&Difficulty> {
+        self.difficulty.as_ref()
+    }
+}
+
+impl MinefieldGenerator for DifficultyGenerator {
+    fn generate(
+        &mut self,
+        params: Parameters,
+        zone: &ExclusionZone,
+    ) -> Result<Area<GroundKind>, GenerationError> {
+        let mut rng = params.rng();
+        let mut seed = params.seed;
+        self.attempts = 0;
+        self.difficulty = None;
+
+        while self.attempts < self.max_attempts {
+            self.attempts += 1;
+            let ground = self.inner.generate(params.with_seed(seed), zone)?;
+
+            // The 3BV is cheap to compute, so skip the solver for boards outside of the range.
+            if self.band.three_bv.contains(&three_bv(&ground)) {
+                let difficulty = Difficulty::analyze(&ground, zone.click);
+                if self.band.contains(&difficulty) {
+                    self.difficulty = Some(difficulty);
+                    return Ok(ground);
+                }
+            }
+            seed = rng.gen();
+        }
+        Err(GenerationError::OutsideDifficultyBand {
+            attempts: self.attempts,
+        })
+    }
+
+    /// Returns how many boards were generated during the last generation.
+    fn attempts(&self) -> Option<usize> {
+        Some(self.attempts)
+    }
+
+    fn score(&self) -> Option<f64> {
+        self.difficulty.as_ref().map(|d| d.score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generator::ImprovedGenerator, solver::MinWithinMaxCombinator};
+
+    fn ground(grid: &str) -> Area<GroundKind> {
+        let width = grid.lines().next().unwrap().len();
+        let height = grid.lines().count();
+        let ground = grid
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .map(|c| match c {
+                'm' => GroundKind::Mine,
+                _ => GroundKind::Dirt,
+            })
+            .collect();
+        Area::with_area(width, height, ground)
+    }
+
+    #[test]
+    fn three_bv_counts_openings_and_isolated_numbers() {
+        let two_openings = ground(
+            "meee
+             eeee
+             eeem",
+        );
+        assert_eq!(three_bv(&two_openings), 2);
+
+        let no_opening = ground("eme");
+        assert_eq!(three_bv(&no_opening), 2);
+
+        let one_opening_and_number = ground(
+            "eeeee
+             eeeee
+             mmmem
+             eeeee",
+        );
+        assert_eq!(three_bv(&one_opening_and_number), 1 + 6);
+    }
+
+    #[test]
+    fn coin_flip_requires_guessing() {
+        let coin_flip = ground(
+            "me
+             ee
+             ee",
+        );
+        let difficulty = Difficulty::analyze(&coin_flip, Location::new(0_usize, 2_usize));
+        assert!(!difficulty.solvable);
+        assert!(difficulty.required_rules.is_empty());
+        assert_eq!(difficulty.score, 2.0 * difficulty.three_bv as f64);
+    }
+
+    #[test]
+    fn generated_board_is_within_band() {
+        let click = Location::new(4_usize, 4_usize);
+        let params = Parameters::new(9, 9, 10).with_seed(3);
+        let band = DifficultyBand::new(10..=30).requiring(&MinWithinMaxCombinator);
+        let mut generator = DifficultyGenerator::new(Box::new(ImprovedGenerator), band.clone());
+
+        let zone = ExclusionZone::for_first_click(&params, click);
+        let ground = generator.generate(params, &zone).unwrap();
+        let difficulty = generator.difficulty().unwrap();
+        assert!(generator.attempts().is_some_and(|a| a >= 1));
+        assert!(band.contains(difficulty), "{:?}", difficulty);
+        assert_eq!(&Difficulty::analyze(&ground, click), difficulty);
+        assert_eq!(generator.score(), Some(difficulty.score));
+    }
+
+    #[test]
+    fn gives_up_outside_of_band() {
+        let params = Parameters::new(9, 9, 10).with_seed(3);
+        let band = DifficultyBand::new(0..=0);
+        let mut generator =
+            DifficultyGenerator::new(Box::new(ImprovedGenerator), band).with_max_attempts(3);
+
+        let zone = ExclusionZone::for_first_click(&params, Location::new(4_usize, 4_usize));
+        assert_eq!(
+            generator.generate(params, &zone),
+            Err(GenerationError::OutsideDifficultyBand { attempts: 3 })
+        );
+        assert_eq!(generator.difficulty(), None);
+        assert_eq!(generator.score(), None);
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::BTreeSet;
+
+use rand::seq::index::sample as rand_sample;
+
+use crate::core::*;
+
+mod board_file;
+mod difficulty;
+mod no_guess;
+mod puzzle;
+mod quality;
+mod weighted;
+
+pub use board_file::*;
+pub use difficulty::*;
+pub use no_guess::*;
+pub use puzzle::*;
+pub use quality::*;
+pub use weighted::*;
+
+/// Returns the indices that must stay free of mines, or an error if the remaining cells
+/// cannot hold all mines.
+fn safe_indices(
+    params: &Parameters,
+    zone: &ExclusionZone,
+) -> Result<BTreeSet<usize>, GenerationError> {
+    let safe_indices = zone.excluded_indices(params);
+    let free_cells = params.width * params.height - safe_indices.len();
+    match params.mine_count <= free_cells {
+        true => Ok(safe_indices),
+        false => Err(GenerationError::TooManyMines {
+            mine_count: params.mine_count,
+            free_cells,
+        }),
+    }
+}
+
+pub struct SimpleGenerator;
+
+impl MinefieldGenerator for SimpleGenerator {
+    fn generate(
+        &mut self,
+        params: Parameters,
+        zone: &ExclusionZone,
+    ) -> Result<Area<GroundKind>, GenerationError> {
+        let Parameters {
+            width,
+            height,
+            mine_count,
+            ..
+        } = params;
+        let safe_indices = safe_indices(&params, zone)?;
+        let candidates: Vec<_> = (0..width * height)
+            .filter(|i| !safe_indices.contains(i))
+            .collect();
+
+        let mut a = Area::new(width, height);
+        let result = rand_sample(&mut params.rng(), candidates.len(), mine_count);
+        for index in result {
+            let mine_location = Location::from_index(candidates[index], width);
+            a[mine_location] = GroundKind::Mine;
+        }
+        Ok(a)
+    }
+}
+
+pub struct ImprovedGenerator;
+
+impl ImprovedGenerator {
+    fn build_safe_location_skipper(safe_indices: BTreeSet<usize>) -> impl Fn(usize) -> usize {
+        move |index| {
+            let mut adjusted_index = index;
+            loop {
+                // count number of skipped safe indices
+                let adjustment = safe_indices
+                    .iter()
+                    .position(|&p| adjusted_index < p)
+                    .unwrap_or(safe_indices.len());
+
+                // if index didn't get a new adjustment, we are done
+                match index + adjustment == adjusted_index {
+                    true => break adjusted_index,
+                    false => adjusted_index = index + adjustment,
+                }
+            }
+        }
+    }
+}
+
+impl MinefieldGenerator for ImprovedGenerator {
+    fn generate(
+        &mut self,
+        params: Parameters,
+        zone: &ExclusionZone,
+    ) -> Result<Area<GroundKind>, GenerationError> {
+        let Parameters {
+            width,
+            height,
+            mine_count,
+            ..
+        } = params;
+        let mut a = Area::new(width, height);
+        let safe_indices = safe_indices(&params, zone)?;
+        let mut result = rand_sample(
+            &mut params.rng(),
+            width * height - safe_indices.len(),
+            mine_count,
+        )
+        .into_vec();
+        result.sort_unstable();
+
+        let skip_safe_indices = Self::build_safe_location_skipper(safe_indices);
+        for index in result {
+            let adjusted_index = skip_safe_indices(index);
+            let mine_location = Location::from_index(adjusted_index, width);
+            a[mine_location] = GroundKind::Mine;
+        }
+        Ok(a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_skipper_maps_indices_correctly() {
+        // Original indices:                Original mine locations:
+        //  00 01 02 03 04 05 06 07 08 09    __ __ __ 03 __ __ __ __ __ __
+        //  10 11 12 13 14+15-16-17+18 19    __ __ __ __ 14+15-__-__+__ __
+        //  20 21 22 23 24|25 26 27|28 29    20 __ __ __ __|__ __ __|__ 29
+        //  30 31 32 33 34+35-36-37+38 39    __ __ __ 33 __+__-__-__+__ 39
+        //  40 41 42 43 44 45 46 47 48 49    __ __ __ __ __ __ __ __ __ __
+        // Adjusted indices:                Adjusted mine locations:
+        //  00 01 02 03 04 05 06 07 08 09    __ __ __ 03 __ __ __ __ __ __
+        //  10 11 12 13 14+--------+15 16    __ __ __ __ 14+--------+15 __
+        //  17 18 19 20 21|        |22 23    __ __ __ 20 __|        |__ __
+        //  24 25 26 27 28+--------+29 30    __ __ __ __ __+--------+29 __
+        //  31 32 33 34 35 36 37 38 39 40    __ __ 33 __ __ __ __ __ 39 __
+        let width = 10;
+        let not_a_mine = Location::from_index(26, width);
+        let params = Parameters::new(width, 5, 10);
+        let zone = ExclusionZone::for_first_click(&params, not_a_mine);
+        let skipper =
+            ImprovedGenerator::build_safe_location_skipper(safe_indices(&params, &zone).unwrap());
+
+        let check = |input, expected_result, msg: &str| {
+            assert_eq!(skipper(input), expected_result, "{}", msg);
+        };
+        check(3, 3, "Invalid adjustment before any safe location.");
+        check(14, 14, "Invalid adjustment before 1st safe location.");
+        check(15, 18, "Invalid adjustment at 1st safe location.");
+        check(20, 23, "Invalid adjustment after 1st safe location block.");
+        check(29, 38, "Invalid adjustment after 2nd safe location block.");
+        check(33, 42, "Invalid adjustment before 3rd safe location block.");
+        check(39, 48, "Invalid adjustment after 3rd safe location block.");
+    }
+
+    fn generators() -> Vec<Box<dyn MinefieldGenerator>> {
+        let weights = parse_weight_map("1 2 3\n4 5 6").unwrap();
+        vec![
+            Box::new(SimpleGenerator),
+            Box::new(ImprovedGenerator),
+            Box::new(ClusteredGenerator::default()),
+            Box::new(GradientGenerator::default()),
+            Box::new(WeightMapGenerator::new(weights)),
+        ]
+    }
+
+    fn mine_count(ground: &Area<GroundKind>) -> usize {
+        ground.iter().filter(|g| g.is_mine()).count()
+    }
+
+    fn generate(
+        generator: &mut Box<dyn MinefieldGenerator>,
+        params: Parameters,
+        click: Location,
+    ) -> Area<GroundKind> {
+        let zone = ExclusionZone::for_first_click(&params, click);
+        generator.generate(params, &zone).unwrap()
+    }
+
+    #[test]
+    fn generators_honour_first_click_policy() {
+        use FirstClickPolicy::*;
+        let click = Location::new(0_usize, 4_usize);
+        for &policy in &[NoGuarantee, SafeCell, Opening, PreOpened] {
+            let params = Parameters::new(10, 5, 30).with_first_click(policy);
+            for mut generator in generators() {
+                let ground = generate(&mut generator, params, click);
+                assert_eq!(
+                    mine_count(&ground),
+                    30,
+                    "Wrong mine count for {:?}.",
+                    policy
+                );
+                for l in policy.safe_area(click) {
+                    let ground_kind = ground.get(l).copied().unwrap_or_default();
+                    assert!(ground_kind.is_dirt(), "Mine at {} for {:?}.", l, policy);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dense_board_weakens_first_click_policy() {
+        // 3x3 board with 8 mines leaves room for a safe cell but not for an opening.
+        let params = Parameters::new(3, 3, 8);
+        let click = Location::new(1_usize, 1_usize);
+        for mut generator in generators() {
+            let ground = generate(&mut generator, params, click);
+            assert_eq!(mine_count(&ground), 8);
+            assert!(ground[click].is_dirt());
+        }
+    }
+
+    #[test]
+    fn same_seed_and_click_yield_same_board() {
+        let click = Location::new(3_usize, 2_usize);
+        let params = Parameters::new(16, 16, 40).with_seed(42);
+        for mut generator in generators() {
+            let first = generate(&mut generator, params, click);
+            let second = generate(&mut generator, params, click);
+            let other_seed = generate(&mut generator, params.with_seed(43), click);
+            assert_eq!(first, second);
+            assert_ne!(first, other_seed);
+        }
+    }
+
+    #[test]
+    fn corner_click_excludes_only_cells_on_the_board() {
+        // An opening in the corner of a 3x3 board excludes 4 cells and leaves exactly 5.
+        let params = Parameters::new(3, 3, 5);
+        let click = Location::new(0_usize, 0_usize);
+        for mut generator in generators() {
+            let ground = generate(&mut generator, params, click);
+            assert_eq!(mine_count(&ground), 5);
+            for l in FirstClickPolicy::Opening.safe_area(click) {
+                assert!(
+                    ground.get(l).is_none_or(GroundKind::is_dirt),
+                    "Mine at {}.",
+                    l
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generators_honour_custom_exclusion_zone() {
+        let params = Parameters::new(4, 4, 12).with_first_click(FirstClickPolicy::NoGuarantee);
+        let excluded: Vec<_> = (0..4_usize).map(|x| Location::new(x, 3_usize)).collect();
+        let zone = ExclusionZone::new(Location::new(0_usize, 3_usize), excluded.clone());
+        for mut generator in generators() {
+            let ground = generator.generate(params, &zone).unwrap();
+            assert_eq!(mine_count(&ground), 12);
+            assert!(excluded.iter().all(|&l| ground[l].is_dirt()));
+        }
+
+        let too_many = Parameters::new(4, 4, 13);
+        for mut generator in generators() {
+            assert_eq!(
+                generator.generate(too_many, &zone),
+                Err(GenerationError::TooManyMines {
+                    mine_count: 13,
+                    free_cells: 12
+                })
+            );
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{core::*, solver::Solver};
+
+/// Wraps another generator and only returns boards that the [`Solver`] can clear
+/// from the first click without ever guessing.
+///
+/// Boards the solver gets stuck on are first repaired by moving single mines away from the
+/// revealed area. If that does not help, a completely new board is generated. If no board
+/// qualifies within the maximum number of attempts, [`GenerationError::NoGuessFree`] is
+/// returned.
+pub struct NoGuessGenerator {
+    inner: Box<dyn MinefieldGenerator>,
+    max_attempts: usize,
+    attempts: usize,
+}
+
+impl NoGuessGenerator {
+    /// Number of local reshuffles that are tried before a board is discarded.
+    const MAX_RESHUFFLES: usize = 20;
+
+    pub fn new(inner: Box<dyn MinefieldGenerator>) -> Self {
+        Self {
+            inner,
+            max_attempts: 10_000,
+            attempts: 0,
+        }
+    }
+
+    /// Limits the number of checked boards, after which generating fails.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Moves a random mine next to the revealed area into the unobserved part of the board.
+    /// Returns `false` if there is no such mine or no free unobserved cell.
+    fn reshuffle<R: Rng>(ground: &mut Area<GroundKind>, fog: &Area<State>, rng: &mut R) -> bool {
+        let is_observed = |l: Location| {
+            std::iter::once(l)
+                .chain(l.neighbours())
+                .any(|n| fog.get(n).is_some_and(State::is_revealed))
+        };
+        let (frontier_mines, interior_dirt): (Vec<_>, Vec<_>) = ground
+            .loc_iter()
+            .filter(|&(l, g)| g.is_mine() == is_observed(l))
+            .partition(|(_, g)| g.is_mine());
+
+        match (frontier_mines.choose(rng), interior_dirt.choose(rng)) {
+            (Some(&(from, _)), Some(&(to, _))) => {
+                ground[from] = GroundKind::Dirt;
+                ground[to] = GroundKind::Mine;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl MinefieldGenerator for NoGuessGenerator {
+    fn generate(
+        &mut self,
+        params: Parameters,
+        zone: &ExclusionZone,
+    ) -> Result<Area<GroundKind>, GenerationError> {
+        let mut rng = params.rng();
+        let mut ground = self.inner.generate(params, zone)?;
+        let mut reshuffles = 0;
+        self.attempts = 1;
+
+        loop {
+            let game = play_without_guessing(ground.clone(), zone.click);
+            if game.state().is_win() {
+                break Ok(ground);
+            }
+            if self.attempts >= self.max_attempts {
+                break Err(GenerationError::NoGuessFree {
+                    attempts: self.attempts,
+                });
+            }
+
+            self.attempts += 1;
+            reshuffles += 1;
+            if reshuffles > Self::MAX_RESHUFFLES
+                || !Self::reshuffle(&mut ground, game.fog(), &mut rng)
+            {
+                reshuffles = 0;
+                ground = self.inner.generate(params.with_seed(rng.gen()), zone)?;
+            }
+        }
+    }
+
+    /// Returns how many boards were checked during the last generation.
+    fn attempts(&self) -> Option<usize> {
+        Some(self.attempts)
+    }
+}
+
+/// Reveals `start` and afterwards reveals and marks every cell the [`Solver`] can deduce
+/// until the game is over or the solver is stuck.
+pub fn play_without_guessing(ground: Area<GroundKind>, start: Location) -> Minefield {
+    play_deductions(ground, start, Solver::solve)
+}
+
+/// Like [`play_without_guessing`] but deduces safe cells and mines with `solve`.
+pub fn play_deductions<F>(ground: Area<GroundKind>, start: Location, solve: F) -> Minefield
+where
+    F: Fn(&Minefield) -> (HashSet<Location>, HashSet<Location>),
+{
+    let mut game = Minefield::with_ground(ground);
+    game.execute(PendingCommand::new(start, Action::Reveal));
+
+    while let GameState::InProgress { .. } = game.state() {
+        let (safe, mines) = solve(&game);
+        let reveals = safe
+            .into_iter()
+            .map(|l| PendingCommand::new(l, Action::Reveal));
+        let marks = mines
+            .into_iter()
+            .map(|l| PendingCommand::new(l, Action::Mark));
+
+        let mut progress = false;
+        for cmd in reveals.chain(marks) {
+            progress |= !matches!(game.execute(cmd), ExecutionResult::Failed);
+        }
+        if !progress {
+            break;
+        }
+    }
+    game
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::ImprovedGenerator;
+
+    #[test]
+    fn generated_boards_are_solvable_without_guessing() {
+        let click = Location::new(4_usize, 4_usize);
+        for seed in 0..5 {
+            let params = Parameters::new(9, 9, 10).with_seed(seed);
+            let mut generator = NoGuessGenerator::new(Box::new(ImprovedGenerator));
+            let zone = ExclusionZone::for_first_click(&params, click);
+            let ground = generator.generate(params, &zone).unwrap();
+
+            assert!(generator.attempts().is_some_and(|a| a >= 1));
+            assert_eq!(ground.iter().filter(|g| g.is_mine()).count(), 10);
+            assert!(play_without_guessing(ground, click).state().is_win());
+        }
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let params = Parameters::new(5, 5, 10).with_seed(1);
+        let mut generator = NoGuessGenerator::new(Box::new(ImprovedGenerator)).with_max_attempts(2);
+        let zone = ExclusionZone::for_first_click(&params, Location::new(0_usize, 0_usize));
+        assert_eq!(
+            generator.generate(params, &zone),
+            Err(GenerationError::NoGuessFree { attempts: 2 })
+        );
+        assert_eq!(generator.attempts(), Some(2));
+    }
+
+    #[test]
+    fn play_without_guessing_stops_at_coin_flip() {
+        // The mine and the dirt in the top row cannot be distinguished.
+        let mut ground = Area::new(2, 3);
+        ground[Location::new(0_usize, 0_usize)] = GroundKind::Mine;
+
+        let game = play_without_guessing(ground, Location::new(0_usize, 2_usize));
+        assert!(matches!(game.state(), GameState::InProgress { .. }));
+        assert!(game.fog()[Location::new(0_usize, 0_usize)].is_hidden());
+        assert!(game.fog()[Location::new(1_usize, 0_usize)].is_hidden());
+    }
+}
@@ -0,0 +1,215 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    core::*,
+    solver::{Rule, Solver},
+};
+
+/// A partially revealed board whose hidden cells can all be deduced without guessing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    /// The position in the grid format of [`Minefield::new_active_game`].
+    pub grid: String,
+    /// Number of hidden cells the player has to deduce.
+    pub hidden: usize,
+    /// Names of the solver rules without which the puzzle cannot be solved.
+    pub required_rules: Vec<&'static str>,
+    /// The hidden cells weighted by the required reasoning: each required rule adds half of them.
+    pub score: f64,
+    /// Number of boards that were tried until one had a unique solution.
+    pub attempts: usize,
+}
+
+impl Puzzle {
+    fn new(ground: &Area<GroundKind>, revealed: &Area<bool>, attempts: usize) -> Self {
+        let hidden = revealed.iter().filter(|&&r| !r).count();
+        let required_rules: Vec<_> = Solver::default_rules()
+            .iter()
+            .map(|r| r.name())
+            .filter(|&name| {
+                let rules_without = || {
+                    Solver::default_rules()
+                        .into_iter()
+                        .filter(|r| r.name() != name)
+                        .collect()
+                };
+                !is_unique(ground, revealed, rules_without)
+            })
+            .collect();
+        let score = hidden as f64 * (1.0 + required_rules.len() as f64 / 2.0);
+        Self {
+            grid: grid(ground, revealed),
+            hidden,
+            required_rules,
+            score,
+            attempts,
+        }
+    }
+
+    /// Returns the grid with all hidden cells shown as `?`, so the mines are not given away.
+    pub fn question(&self) -> String {
+        self.grid.replace(['m', 'e'], "?")
+    }
+
+    /// Returns the puzzle as a game that can be played or solved.
+    pub fn game(&self) -> Minefield {
+        Minefield::new_active_game(&self.grid)
+    }
+}
+
+/// Generates [`Puzzle`]s from the boards of another generator.
+///
+/// All dirt of a board starts out revealed, afterwards the revealed cells are hidden one by one
+/// in random order as long as the [`Solver`] can still deduce every hidden cell.
+/// This leaves a position where no revealed cell can be hidden without losing the unique solution.
+pub struct PuzzleGenerator {
+    inner: Box<dyn MinefieldGenerator>,
+    max_attempts: usize,
+}
+
+impl PuzzleGenerator {
+    pub fn new(inner: Box<dyn MinefieldGenerator>) -> Self {
+        Self {
+            inner,
+            max_attempts: 100,
+        }
+    }
+
+    /// Limits the number of boards that are tried if the fully revealed board is not unique,
+    /// which happens if mines are enclosed by other mines.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Generates a puzzle, the same parameters always yield the same puzzle.
+    ///
+    /// Returns [`GenerationError::NoUniquePuzzle`] if none of the generated boards has a unique
+    /// solution.
+    pub fn generate(&mut self, params: Parameters) -> Result<Puzzle, GenerationError> {
+        let mut rng = params.rng();
+        rng.set_stream(PUZZLE_STREAM);
+        // Puzzles do not start with a click, so no cells need to be excluded.
+        let zone = ExclusionZone::new(Location::new(params.width / 2, params.height / 2), None);
+
+        for attempt in 1..=self.max_attempts {
+            let ground = self.inner.generate(params.with_seed(rng.gen()), &zone)?;
+            let mut revealed: Area<bool> = Area::with_area(
+                ground.width(),
+                ground.height(),
+                ground.iter().map(GroundKind::is_dirt).collect(),
+            );
+            if !is_unique(&ground, &revealed, Solver::default_rules) {
+                continue;
+            }
+
+            let mut order: Vec<_> = ground
+                .loc_iter()
+                .filter(|(_, g)| g.is_dirt())
+                .map(|(l, _)| l)
+                .collect();
+            order.shuffle(&mut rng);
+            for l in order {
+                revealed[l] = false;
+                if !is_unique(&ground, &revealed, Solver::default_rules) {
+                    revealed[l] = true;
+                }
+            }
+            return Ok(Puzzle::new(&ground, &revealed, attempt));
+        }
+        Err(GenerationError::NoUniquePuzzle {
+            attempts: self.max_attempts,
+        })
+    }
+}
+
+/// The random number stream used to pick the boards and the order in which cells are hidden.
+const PUZZLE_STREAM: u64 = 3;
+
+/// Returns `true` if the solver deduces every hidden cell of the position with the given rules.
+fn is_unique<F>(ground: &Area<GroundKind>, revealed: &Area<bool>, rules: F) -> bool
+where
+    F: Fn() -> Vec<Box<dyn Rule>>,
+{
+    let game = Minefield::new_active_game(&grid(ground, revealed));
+    let (safe, mines) = Solver::solve_with_rules(&game, rules());
+    revealed
+        .loc_iter()
+        .filter(|(_, &r)| !r)
+        .all(|(l, _)| safe.contains(&l) || mines.contains(&l))
+}
+
+/// Writes the position in the grid format of [`Minefield::new_active_game`].
+fn grid(ground: &Area<GroundKind>, revealed: &Area<bool>) -> String {
+    let mut grid = String::with_capacity((ground.width() + 1) * ground.height());
+    for (l, g) in ground.loc_iter() {
+        let c = match (g, revealed[l]) {
+            (GroundKind::Mine, _) => 'm',
+            (GroundKind::Dirt, false) => 'e',
+            (GroundKind::Dirt, true) => {
+                let adj_mines = l
+                    .neighbours()
+                    .filter(|&n| ground.get(n).is_some_and(GroundKind::is_mine))
+                    .count();
+                std::char::from_digit(adj_mines as u32, 10).expect("At most 8 neighbours.")
+            }
+        };
+        grid.push(c);
+        if l.as_tuple().is_some_and(|(x, _)| x + 1 == ground.width()) {
+            grid.push('\n');
+        }
+    }
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::ImprovedGenerator;
+
+    #[test]
+    fn puzzle_is_unique_and_minimal() {
+        let params = Parameters::new(5, 5, 4).with_seed(5);
+        let puzzle = PuzzleGenerator::new(Box::new(ImprovedGenerator))
+            .generate(params)
+            .unwrap();
+        let game = puzzle.game();
+        assert_eq!(game.mine_count(), 4);
+        assert!(puzzle.hidden > 4);
+        assert!(puzzle.score >= puzzle.hidden as f64);
+        assert!(puzzle.attempts >= 1);
+
+        let hidden: Vec<_> = game
+            .fog()
+            .loc_iter()
+            .filter(|(_, s)| s.is_hidden())
+            .map(|(l, _)| l)
+            .collect();
+        assert_eq!(hidden.len(), puzzle.hidden);
+        assert_eq!(puzzle.question().matches('?').count(), puzzle.hidden);
+        let (safe, mines) = Solver::solve(&game);
+        assert_eq!(safe.len() + mines.len(), puzzle.hidden);
+        assert_eq!(mines.len(), 4);
+
+        // Hiding any further revealed cell loses the unique solution.
+        let revealed = game.fog().loc_iter().filter(|(_, s)| s.is_revealed());
+        for (l, _) in revealed {
+            let mut game = puzzle.game();
+            game.unreveal(l);
+            let (safe, mines) = Solver::solve(&game);
+            assert!(safe.len() + mines.len() < puzzle.hidden + 1, "{}", l);
+        }
+    }
+
+    #[test]
+    fn same_parameters_yield_same_puzzle() {
+        let params = Parameters::new(5, 5, 4).with_seed(11);
+        let first = PuzzleGenerator::new(Box::new(ImprovedGenerator))
+            .generate(params)
+            .unwrap();
+        let second = PuzzleGenerator::new(Box::new(ImprovedGenerator))
+            .generate(params)
+            .unwrap();
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,216 @@
+use std::{collections::BTreeMap, fmt};
+
+use rand::Rng;
+
+use crate::core::*;
+
+use super::three_bv;
+
+/// Standard normal quantile above which a deviation counts as significant, i.e. `p < 0.001`.
+const SIGNIFICANT_Z_SCORE: f64 = 3.09;
+
+/// Empirical statistics about the boards of a generator.
+#[derive(Clone, Debug)]
+pub struct QualityReport {
+    pub runs: usize,
+    /// The fraction of boards with a mine on each cell.
+    pub frequencies: Area<f64>,
+    /// Number of mines that were placed in the exclusion zone.
+    pub excluded_mines: usize,
+    /// Chi-square statistic of the mine counts of all `k` cells outside of the exclusion zone.
+    ///
+    /// The mines of a board are drawn without replacement, so the count of a cell has the
+    /// variance `runs * p * (1 - p)` and the counts of two cells are negatively correlated
+    /// because every board has the same number of mines. The squared deviations divided by the
+    /// variance are therefore `k / (k - 1)` times a chi-square variable with `k - 1` degrees of
+    /// freedom, which this statistic corrects for.
+    pub chi_square: f64,
+    pub degrees_of_freedom: usize,
+    /// The chi-square statistic transformed to a standard normal score with the Wilson-Hilferty
+    /// approximation.
+    pub z_score: f64,
+    /// Histogram of the number of cells revealed by the first click.
+    pub opening_sizes: BTreeMap<usize, usize>,
+    /// Histogram of the 3BV of the boards.
+    pub three_bv: BTreeMap<usize, usize>,
+}
+
+impl QualityReport {
+    /// Returns `true` if mines were placed in the exclusion zone or the mines are not spread
+    /// uniformly over the other cells with a significance level of 0.1%.
+    pub fn is_biased(&self) -> bool {
+        self.excluded_mines > 0 || self.z_score > SIGNIFICANT_Z_SCORE
+    }
+}
+
+/// Generates `runs` boards with seeds derived from `params` and collects statistics about them.
+pub fn check_quality(
+    generator: &mut dyn MinefieldGenerator,
+    params: Parameters,
+    zone: &ExclusionZone,
+    runs: usize,
+) -> Result<QualityReport, GenerationError> {
+    let mut rng = params.rng();
+    let mut mine_counts = Area::<usize>::new(params.width, params.height);
+    let mut opening_sizes = BTreeMap::new();
+    let mut three_bvs = BTreeMap::new();
+
+    for _ in 0..runs {
+        let ground = generator.generate(params.with_seed(rng.gen()), zone)?;
+        for (count, g) in mine_counts.iter_mut().zip(ground.iter()) {
+            *count += g.is_mine() as usize;
+        }
+        *three_bvs.entry(three_bv(&ground)).or_insert(0) += 1;
+
+        let mut game = Minefield::with_ground(ground);
+        game.execute(PendingCommand::new(zone.click, Action::Reveal));
+        let opening_size = game.fog().iter().filter(|s| s.is_revealed()).count();
+        *opening_sizes.entry(opening_size).or_insert(0) += 1;
+    }
+
+    let excluded = zone.excluded_indices(&params);
+    let excluded_mines = mine_counts
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| excluded.contains(i))
+        .map(|(_, &c)| c)
+        .sum();
+
+    let free_counts: Vec<_> = mine_counts
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !excluded.contains(i))
+        .map(|(_, &c)| c as f64)
+        .collect();
+    let p = params.mine_count as f64 / free_counts.len().max(1) as f64;
+    let expected = runs as f64 * p;
+    let variance = expected * (1.0 - p);
+    let degrees_of_freedom = free_counts.len().saturating_sub(1);
+    let chi_square = match variance > 0.0 {
+        true => {
+            let squared_deviations: f64 = free_counts
+                .iter()
+                .map(|c| (c - expected).powi(2) / variance)
+                .sum();
+            squared_deviations * degrees_of_freedom as f64 / free_counts.len() as f64
+        }
+        false => 0.0,
+    };
+
+    let k = degrees_of_freedom.max(1) as f64;
+    let z_score = ((chi_square / k).cbrt() - (1.0 - 2.0 / (9.0 * k))) / (2.0 / (9.0 * k)).sqrt();
+
+    let frequencies = mine_counts
+        .iter()
+        .map(|&c| c as f64 / runs.max(1) as f64)
+        .collect();
+    Ok(QualityReport {
+        runs,
+        frequencies: Area::with_area(params.width, params.height, frequencies),
+        excluded_mines,
+        chi_square,
+        degrees_of_freedom,
+        z_score,
+        opening_sizes,
+        three_bv: three_bvs,
+    })
+}
+
+impl fmt::Display for QualityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Mine frequency per cell in % over {} runs:", self.runs)?;
+        for row in self.frequencies.rows() {
+            for frequency in row {
+                write!(f, "{:6.1}", frequency * 100.0)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "Mines in exclusion zone: {}", self.excluded_mines)?;
+        writeln!(
+            f,
+            "Chi-square: {:.1} with {} degrees of freedom, z-score {:.2}",
+            self.chi_square, self.degrees_of_freedom, self.z_score
+        )?;
+
+        let histogram = |f: &mut fmt::Formatter, name, histogram: &BTreeMap<usize, usize>| {
+            writeln!(f, "{}:", name)?;
+            for (value, count) in histogram {
+                writeln!(f, "{:>5}: {}", value, count)?;
+            }
+            Ok(())
+        };
+        histogram(f, "Opening size", &self.opening_sizes)?;
+        histogram(f, "3BV", &self.three_bv)?;
+
+        match self.is_biased() {
+            true => writeln!(f, "BIASED"),
+            false => writeln!(f, "No significant bias"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{GradientGenerator, ImprovedGenerator, SimpleGenerator};
+
+    fn check(generator: &mut dyn MinefieldGenerator, click: Location) -> QualityReport {
+        let params = Parameters::new(6, 5, 7).with_seed(1);
+        let zone = ExclusionZone::for_first_click(&params, click);
+        check_quality(generator, params, &zone, 2000).unwrap()
+    }
+
+    fn excluded_cells(click: Location) -> usize {
+        FirstClickPolicy::Opening
+            .safe_area(click)
+            .into_iter()
+            .filter(|l| l.as_tuple().is_some_and(|(x, y)| x < 6 && y < 5))
+            .count()
+    }
+
+    #[test]
+    fn uniform_generators_are_not_biased() {
+        for &click in &[
+            Location::new(0_usize, 0_usize),
+            Location::new(2_usize, 3_usize),
+        ] {
+            for generator in &mut [
+                &mut SimpleGenerator as &mut dyn MinefieldGenerator,
+                &mut ImprovedGenerator,
+            ] {
+                let report = check(*generator, click);
+                assert!(!report.is_biased(), "{}", report);
+                assert_eq!(report.excluded_mines, 0);
+                assert_eq!(report.degrees_of_freedom, 30 - excluded_cells(click) - 1);
+                assert_eq!(report.opening_sizes.values().sum::<usize>(), 2000);
+                assert_eq!(report.three_bv.values().sum::<usize>(), 2000);
+            }
+        }
+    }
+
+    #[test]
+    fn chi_square_of_uniform_boards_has_the_mean_of_its_distribution() {
+        let params = Parameters::new(4, 3, 3);
+        let zone = ExclusionZone::new(Location::new(0_usize, 0_usize), None);
+        let samples = 800;
+        let reports: Vec<_> = (0..samples)
+            .map(|seed| {
+                check_quality(&mut SimpleGenerator, params.with_seed(seed), &zone, 100).unwrap()
+            })
+            .collect();
+        let mean = reports.iter().map(|r| r.chi_square).sum::<f64>() / samples as f64;
+        // a chi-square variable has its degrees of freedom as mean, the standard error of the
+        // mean is sqrt(2 * 11 / 800) = 0.17
+        assert_eq!(reports[0].degrees_of_freedom, 11);
+        assert!((mean - 11.0).abs() < 0.5, "{}", mean);
+    }
+
+    #[test]
+    fn gradient_generator_is_biased() {
+        let report = check(
+            &mut GradientGenerator::default(),
+            Location::new(0_usize, 0_usize),
+        );
+        assert!(report.is_biased(), "{}", report);
+    }
+}
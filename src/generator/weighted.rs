@@ -0,0 +1,296 @@
+use std::{fs, io, path::Path};
+
+use rand::{seq::index::sample_weighted, Rng};
+
+use crate::core::*;
+
+use super::safe_indices;
+
+/// Places exactly `params.mine_count` mines outside of the exclusion zone, each cell
+/// is picked with a probability proportional to its weight.
+///
+/// Cells with weight zero only receive mines if there are not enough other cells.
+fn place_weighted<F>(
+    params: &Parameters,
+    zone: &ExclusionZone,
+    weight: F,
+) -> Result<Area<GroundKind>, GenerationError>
+where
+    F: Fn(Location) -> f64,
+{
+    let Parameters {
+        width,
+        height,
+        mine_count,
+        ..
+    } = *params;
+    let safe_indices = safe_indices(params, zone)?;
+    let candidates: Vec<_> = (0..width * height)
+        .filter(|i| !safe_indices.contains(i))
+        .map(|i| Location::from_index(i, width))
+        .collect();
+
+    // Use a separate stream so the weights can be randomized with the main stream.
+    let mut rng = params.rng();
+    rng.set_stream(WEIGHTED_PLACEMENT_STREAM);
+    let weight = |i: usize| weight(candidates[i]).max(0.0);
+    let result = sample_weighted(&mut rng, candidates.len(), weight, mine_count)
+        .expect("Weights are never negative.");
+
+    let mut a = Area::new(width, height);
+    for index in result {
+        a[candidates[index]] = GroundKind::Mine;
+    }
+    Ok(a)
+}
+
+/// The random number stream used to pick the mines from the weighted cells.
+const WEIGHTED_PLACEMENT_STREAM: u64 = 2;
+
+/// Places mines in clusters around randomly chosen centers.
+#[derive(Clone, Copy, Debug)]
+pub struct ClusteredGenerator {
+    /// Average number of mines per cluster.
+    pub mines_per_cluster: usize,
+    /// Standard deviation of the distance of mines to their cluster center.
+    pub spread: f64,
+}
+
+impl Default for ClusteredGenerator {
+    fn default() -> Self {
+        Self {
+            mines_per_cluster: 8,
+            spread: 1.5,
+        }
+    }
+}
+
+impl MinefieldGenerator for ClusteredGenerator {
+    fn generate(
+        &mut self,
+        params: Parameters,
+        zone: &ExclusionZone,
+    ) -> Result<Area<GroundKind>, GenerationError> {
+        let mut rng = params.rng();
+        let cluster_count = (params.mine_count / self.mines_per_cluster.max(1)).max(1);
+        let centers: Vec<_> = (0..cluster_count)
+            .map(|_| {
+                let x = rng.gen_range(0.0..params.width as f64);
+                let y = rng.gen_range(0.0..params.height as f64);
+                (x, y)
+            })
+            .collect();
+
+        let variance = 2.0 * self.spread * self.spread;
+        place_weighted(&params, zone, |l| {
+            let (x, y) = l.as_tuple().expect("Candidates are valid locations.");
+            let density: f64 = centers
+                .iter()
+                .map(|&(cx, cy)| {
+                    let distance = (x as f64 - cx).powi(2) + (y as f64 - cy).powi(2);
+                    (-distance / variance).exp()
+                })
+                .sum();
+            // A small base density keeps all cells possible.
+            density + 0.01
+        })
+    }
+}
+
+/// Places mines with a density that grows linearly across the board.
+#[derive(Clone, Copy, Debug)]
+pub struct GradientGenerator {
+    /// The direction in which the density grows.
+    pub direction: Direction,
+    /// How many times denser the last row or column is compared to the first one.
+    pub steepness: f64,
+}
+
+impl Default for GradientGenerator {
+    fn default() -> Self {
+        Self {
+            direction: Direction::Right,
+            steepness: 10.0,
+        }
+    }
+}
+
+impl MinefieldGenerator for GradientGenerator {
+    fn generate(
+        &mut self,
+        params: Parameters,
+        zone: &ExclusionZone,
+    ) -> Result<Area<GroundKind>, GenerationError> {
+        let Self {
+            direction,
+            steepness,
+        } = *self;
+        let (width, height) = (params.width, params.height);
+        place_weighted(&params, zone, |l| {
+            let (x, y) = l.as_tuple().expect("Candidates are valid locations.");
+            let (position, length) = match direction {
+                Direction::Right => (x, width),
+                Direction::Left => (width - 1 - x, width),
+                Direction::Down => (y, height),
+                Direction::Up => (height - 1 - y, height),
+            };
+            let progress = position as f64 / length.saturating_sub(1).max(1) as f64;
+            1.0 + (steepness - 1.0) * progress
+        })
+    }
+}
+
+/// Places mines with a density given by a weight map, the map is stretched to the board size.
+#[derive(Clone, Debug)]
+pub struct WeightMapGenerator {
+    weights: Area<f64>,
+}
+
+impl WeightMapGenerator {
+    /// # Panics
+    /// Panics if `weights` is empty.
+    pub fn new(weights: Area<f64>) -> Self {
+        assert!(!weights.is_empty(), "The weight map must not be empty.");
+        Self { weights }
+    }
+
+    /// Loads a weight map file, each line holds one row of non-negative numbers separated by whitespace.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let invalid = |msg: String| {
+            let msg = format!("Invalid weight map {}: {}", path.display(), msg);
+            io::Error::new(io::ErrorKind::InvalidData, msg)
+        };
+        let content = fs::read_to_string(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        parse_weight_map(&content).map(Self::new).map_err(invalid)
+    }
+}
+
+impl MinefieldGenerator for WeightMapGenerator {
+    fn generate(
+        &mut self,
+        params: Parameters,
+        zone: &ExclusionZone,
+    ) -> Result<Area<GroundKind>, GenerationError> {
+        let weights = &self.weights;
+        let scale = |n: usize, from: usize, to: usize| n * to / from;
+        place_weighted(&params, zone, |l| {
+            let (x, y) = l.as_tuple().expect("Candidates are valid locations.");
+            let x = scale(x, params.width, weights.width());
+            let y = scale(y, params.height, weights.height());
+            weights[Location::new(x, y)]
+        })
+    }
+}
+
+/// Parses rows of whitespace separated non-negative numbers, empty lines are skipped.
+pub fn parse_weight_map(content: &str) -> Result<Area<f64>, String> {
+    let rows = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .map(|(y, row)| {
+            row.split_whitespace()
+                .map(|w| match w.parse::<f64>() {
+                    Ok(w) if w >= 0.0 && w.is_finite() => Ok(w),
+                    _ => Err(format!("Invalid weight {:?} in row {}.", w, y)),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let width = rows.first().map_or(0, Vec::len);
+    if width == 0 {
+        return Err("Weight map is empty.".into());
+    }
+    if let Some(y) = rows.iter().position(|r| r.len() != width) {
+        return Err(format!("Row {} does not have {} weights.", y, width));
+    }
+    let height = rows.len();
+    Ok(Area::with_area(width, height, rows.concat()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::SimpleGenerator;
+
+    fn mines(ground: &Area<GroundKind>) -> impl Iterator<Item = Location> + '_ {
+        ground
+            .loc_iter()
+            .filter(|(_, g)| g.is_mine())
+            .map(|(l, _)| l)
+    }
+
+    #[test]
+    fn gradient_is_denser_in_its_direction() {
+        let params = Parameters::new(20, 20, 80).with_seed(1);
+        let click = Location::new(10_usize, 10_usize);
+        for &direction in &[
+            Direction::Left,
+            Direction::Right,
+            Direction::Up,
+            Direction::Down,
+        ] {
+            let mut generator = GradientGenerator {
+                direction,
+                ..Default::default()
+            };
+            let zone = ExclusionZone::for_first_click(&params, click);
+            let ground = generator.generate(params, &zone).unwrap();
+            let along = |l: Location| {
+                let (x, y) = l.as_tuple().unwrap();
+                match direction {
+                    Direction::Right => x,
+                    Direction::Left => 19 - x,
+                    Direction::Down => y,
+                    Direction::Up => 19 - y,
+                }
+            };
+            let dense = mines(&ground).filter(|&l| along(l) >= 10).count();
+            let sparse = 80 - dense;
+            assert!(
+                2 * dense > 3 * sparse,
+                "{:?}: {} vs {}",
+                direction,
+                dense,
+                sparse
+            );
+        }
+    }
+
+    #[test]
+    fn clusters_have_more_adjacent_mines_than_uniform_boards() {
+        let params = Parameters::new(30, 16, 99).with_seed(7);
+        let click = Location::new(0_usize, 0_usize);
+        let adjacent_mines = |ground: &Area<GroundKind>| {
+            mines(ground)
+                .flat_map(|l| l.neighbours())
+                .filter(|&n| ground.get(n).is_some_and(GroundKind::is_mine))
+                .count()
+        };
+
+        let zone = ExclusionZone::for_first_click(&params, click);
+        let clustered = ClusteredGenerator::default()
+            .generate(params, &zone)
+            .unwrap();
+        let uniform = SimpleGenerator.generate(params, &zone).unwrap();
+        assert!(2 * adjacent_mines(&clustered) > 3 * adjacent_mines(&uniform));
+    }
+
+    #[test]
+    fn weight_map_is_stretched_to_board() {
+        let weights = parse_weight_map("0 1\n\n0 1\n").unwrap();
+        let mut generator = WeightMapGenerator::new(weights);
+        let params = Parameters::new(10, 4, 12).with_first_click(FirstClickPolicy::NoGuarantee);
+        let zone = ExclusionZone::new(Location::new(0_usize, 0_usize), None);
+        let ground = generator.generate(params, &zone).unwrap();
+        assert!(mines(&ground).all(|l| l.as_tuple().unwrap().0 >= 5));
+
+        assert!(parse_weight_map("").is_err());
+        assert!(parse_weight_map("1 2\n3").is_err());
+        assert!(parse_weight_map("1 -2").is_err());
+    }
+}
@@ -1,11 +1,485 @@
+// Large parts of the core and solver API are only exercised by tests so far.
+#![allow(dead_code)]
+
+use std::{fs::File, io::BufWriter, num::NonZeroUsize, path::Path, str::FromStr, thread};
+
+use clap::{value_t, App, Arg, ArgMatches, Error, ErrorKind};
 use frontend::Term;
 
+use crate::{
+    bot::{simulate, Preset},
+    core::{
+        daily_parameters, Date, ExclusionZone, FirstClickPolicy, Location, Minefield,
+        MinefieldGenerator, Parameters, ResultCode,
+    },
+    generator::{
+        check_quality, BoardFileGenerator, ClusteredGenerator, DifficultyBand, DifficultyGenerator,
+        GradientGenerator, ImprovedGenerator, NoGuessGenerator, OnRestart, PuzzleGenerator,
+        SimpleGenerator, WeightMapGenerator,
+    },
+    solver::{
+        analyze, benchmark, differential_test, short_name, DpllBackend, GaussBackend, RuleBackend,
+        Solver, SolverBackend,
+    },
+};
+
+mod bot;
 mod core;
 mod frontend;
 mod generator;
 mod solver;
 
 fn main() {
-    let mut term = Term::new((None, None), None);
+    let rules: Vec<_> = Solver::default_rules()
+        .iter()
+        .map(|r| r.short_name())
+        .collect();
+    let matches =
+        App::new("sweepers")
+            .about("A minesweeper implementation for the terminal.")
+            .arg(
+                Arg::with_name("width")
+                    .long("width")
+                    .takes_value(true)
+                    .help("Number of columns, defaults to the terminal width"),
+            )
+            .arg(
+                Arg::with_name("height")
+                    .long("height")
+                    .takes_value(true)
+                    .help("Number of rows, defaults to the terminal height"),
+            )
+            .arg(
+                Arg::with_name("mines")
+                    .long("mines")
+                    .takes_value(true)
+                    .help("Number of mines, defaults to a sixth of all cells"),
+            )
+            .arg(Arg::with_name("seed").long("seed").takes_value(true).help(
+                "Seed for the first board, the same seed and first click yield the same board",
+            ))
+            .arg(
+                Arg::with_name("generator")
+                    .long("generator")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .possible_values(&["simple", "improved", "no-guess", "clustered", "gradient"])
+                    .default_value("improved")
+                    .help("Algorithm that places the mines, may be repeated with --simulate"),
+            )
+            .arg(
+                Arg::with_name("first-click")
+                    .long("first-click")
+                    .takes_value(true)
+                    .possible_values(&["none", "safe-cell", "opening", "pre-opened"])
+                    .help("How the first reveal is protected from mines, defaults to opening"),
+            )
+            .arg(
+                Arg::with_name("max-attempts")
+                    .long("max-attempts")
+                    .takes_value(true)
+                    .help("Number of boards no-guess, the difficulty limits and --puzzle try before they give up, defaults to 10000 and 100 for --puzzle"),
+            )
+            .arg(
+                Arg::with_name("weight-map")
+                    .long("weight-map")
+                    .takes_value(true)
+                    .help("Place mines with the cell weights from this file instead of uniformly"),
+            )
+            .arg(
+                Arg::with_name("min-3bv")
+                    .long("min-3bv")
+                    .takes_value(true)
+                    .help("Only accept boards that take at least this many clicks to clear"),
+            )
+            .arg(
+                Arg::with_name("max-3bv")
+                    .long("max-3bv")
+                    .takes_value(true)
+                    .help("Only accept boards that take at most this many clicks to clear"),
+            )
+            .arg(
+                Arg::with_name("require-rule")
+                    .long("require-rule")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .possible_values(&rules)
+                    .help("Only accept boards that cannot be cleared without this solver rule"),
+            )
+            .arg(
+                Arg::with_name("board-file")
+                    .long("board-file")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .conflicts_with_all(&["min-3bv", "max-3bv", "require-rule"])
+                    .help("Play the fixed boards from this file instead of random ones"),
+            )
+            .arg(
+                Arg::with_name("on-restart")
+                    .long("on-restart")
+                    .takes_value(true)
+                    .requires("board-file")
+                    .possible_values(&["next", "replay"])
+                    .help("Whether restarting plays the next board or the same one again"),
+            )
+            .arg(
+                Arg::with_name("puzzle")
+                    .long("puzzle")
+                    .help("Print a puzzle with a unique solution instead of starting a game"),
+            )
+            .arg(
+                Arg::with_name("trace")
+                    .long("trace")
+                    .takes_value(true)
+                    .value_name("path")
+                    .requires("puzzle")
+                    .help("Write the solver's facts after every iteration of solving the puzzle to this file"),
+            )
+            .arg(
+                Arg::with_name("dump-facts")
+                    .long("dump-facts")
+                    .takes_value(true)
+                    .value_name("path")
+                    .requires("puzzle")
+                    .help("Write the solver's final facts about the puzzle to this file, as a Graphviz graph if it ends with .dot"),
+            )
+            .arg(
+                Arg::with_name("check-quality")
+                    .long("check-quality")
+                    .takes_value(true)
+                    .value_name("runs")
+                    .help("Print statistics about this many boards of the generator instead of starting a game"),
+            )
+            .arg(
+                Arg::with_name("check-solver")
+                    .long("check-solver")
+                    .takes_value(true)
+                    .value_name("positions")
+                    .help("Compare the solver with a brute force oracle on this many random positions, defaults to 5x5 with 5 mines"),
+            )
+            .arg(
+                Arg::with_name("bench-solver")
+                    .long("bench-solver")
+                    .takes_value(true)
+                    .value_name("positions")
+                    .help("Time all solver backends on this many random positions, defaults to 24x7 with 28 mines"),
+            )
+            .arg(
+                Arg::with_name("solver")
+                    .long("solver")
+                    .takes_value(true)
+                    .possible_values(&["rules", "dpll", "gauss"])
+                    .help("Solver backend of --check-solver and --simulate, defaults to rules"),
+            )
+            .arg(
+                Arg::with_name("daily")
+                    .long("daily")
+                    .takes_value(true)
+                    .min_values(0)
+                    .value_name("YYYY-MM-DD")
+                    // the daily board is the same for everybody, so nothing about it can be chosen
+                    .conflicts_with_all(&[
+                        "width", "height", "mines", "seed", "generator", "first-click", "max-attempts",
+                        "weight-map", "min-3bv", "max-3bv", "require-rule", "puzzle",
+                        "check-quality", "check-solver", "bench-solver", "board-file",
+                    ])
+                    .help("Play the daily challenge of today or the given date, the same board for everybody"),
+            )
+            .arg(
+                Arg::with_name("compare")
+                    .long("compare")
+                    .takes_value(true)
+                    .value_name("code")
+                    .requires("daily")
+                    .help("Compare the result of the daily challenge with somebody else's result code"),
+            )
+            .arg(
+                Arg::with_name("simulate")
+                    .long("simulate")
+                    .takes_value(true)
+                    .value_name("games")
+                    .conflicts_with_all(&["puzzle", "check-quality", "daily", "board-file", "min-3bv", "max-3bv", "require-rule"])
+                    .help("Let a bot play this many games per preset and generator and print its win rate"),
+            )
+            .arg(
+                Arg::with_name("preset")
+                    .long("preset")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .requires("simulate")
+                    .possible_values(&["beginner", "intermediate", "expert"])
+                    .help("Board size of the simulated games, may be repeated, defaults to beginner"),
+            )
+            .arg(
+                Arg::with_name("threads")
+                    .long("threads")
+                    .takes_value(true)
+                    .requires("simulate")
+                    .help("Number of threads that play the simulated games, defaults to the number of CPUs"),
+            )
+            .get_matches();
+
+    let width = optional_value::<usize>(&matches, "width");
+    let height = optional_value::<usize>(&matches, "height");
+    let mines = optional_value::<usize>(&matches, "mines");
+    let max_attempts = optional_value::<usize>(&matches, "max-attempts");
+    if matches.is_present("daily") {
+        let date = optional_value::<Date>(&matches, "daily").unwrap_or_else(Date::today);
+        play_daily(date, optional_value(&matches, "compare"));
+        return;
+    }
+    if matches.is_present("puzzle") {
+        let mut params =
+            Parameters::new(width.unwrap_or(9), height.unwrap_or(9), mines.unwrap_or(10));
+        if let Some(seed) = optional_value::<u64>(&matches, "seed") {
+            params = params.with_seed(seed);
+        }
+        let mut generator = PuzzleGenerator::new(Box::new(ImprovedGenerator));
+        if let Some(max_attempts) = max_attempts {
+            generator = generator.with_max_attempts(max_attempts);
+        }
+        let puzzle = generator.generate(params).unwrap_or_else(|e| {
+            Error::with_description(&e.to_string(), ErrorKind::InvalidValue).exit()
+        });
+        let rules: Vec<_> = puzzle
+            .required_rules
+            .iter()
+            .map(|r| short_name(r))
+            .collect();
+        println!("{}", puzzle.question());
+        println!("Solution:\n{}", puzzle.grid);
+        println!(
+            "Seed: {}, Attempts: {}, Hidden cells: {}, Required rules: {:?}, Score: {}",
+            params.seed, puzzle.attempts, puzzle.hidden, rules, puzzle.score
+        );
+
+        let game = puzzle.game();
+        let analysis = match matches.value_of("trace") {
+            Some(path) => {
+                let file = File::create(path).unwrap_or_else(|e| {
+                    Error::with_description(&e.to_string(), ErrorKind::Io).exit()
+                });
+                Solver::analyze_traced(&game, Solver::default_rules(), &mut BufWriter::new(file))
+            }
+            None => analyze(&game),
+        };
+        if let Some(path) = matches.value_of("dump-facts") {
+            Solver::dump_facts(&game, Solver::default_rules(), Path::new(path))
+                .unwrap_or_else(|e| Error::with_description(&e.to_string(), ErrorKind::Io).exit());
+        }
+        let facts_per_rule: Vec<_> = analysis
+            .facts_per_rule
+            .iter()
+            .map(|(&rule, facts)| format!("{}: {}", short_name(rule), facts))
+            .collect();
+        println!(
+            "Solver iterations: {}, Facts per rule: {}",
+            analysis.iterations,
+            facts_per_rule.join(", ")
+        );
+        return;
+    }
+    let weight_map = matches.value_of("weight-map").map(|path| {
+        WeightMapGenerator::load(path)
+            .unwrap_or_else(|e| Error::with_description(&e.to_string(), ErrorKind::Io).exit())
+    });
+    let generators: Vec<_> = matches
+        .values_of("generator")
+        .map(Iterator::collect)
+        .unwrap_or_default();
+    let backend = named_backend(matches.value_of("solver").unwrap_or("rules"));
+    let first_click = named_first_click(matches.value_of("first-click").unwrap_or("opening"));
+    if let Some(positions) = optional_value::<usize>(&matches, "check-solver") {
+        let mut params =
+            Parameters::new(width.unwrap_or(5), height.unwrap_or(5), mines.unwrap_or(5));
+        if let Some(seed) = optional_value::<u64>(&matches, "seed") {
+            params = params.with_seed(seed);
+        }
+        let report = differential_test(params, positions, backend.as_ref());
+        print!("{}", report);
+        return;
+    }
+    if let Some(positions) = optional_value::<usize>(&matches, "bench-solver") {
+        let mut params = Parameters::new(
+            width.unwrap_or(24),
+            height.unwrap_or(7),
+            mines.unwrap_or(28),
+        );
+        if let Some(seed) = optional_value::<u64>(&matches, "seed") {
+            params = params.with_seed(seed);
+        }
+        for result in benchmark(
+            params,
+            positions,
+            &[&RuleBackend, &GaussBackend, &DpllBackend],
+        ) {
+            println!("{}", result);
+        }
+        return;
+    }
+    if let Some(games) = optional_value::<usize>(&matches, "simulate") {
+        let presets: Vec<Preset> = match matches.values_of("preset") {
+            Some(presets) => presets
+                .map(|p| p.parse().expect("Presets are validated by clap."))
+                .collect(),
+            None => vec![Preset::Beginner],
+        };
+        let generators = match matches.occurrences_of("generator") {
+            0 => vec!["simple", "improved"],
+            _ => generators,
+        };
+        let threads = optional_value::<usize>(&matches, "threads")
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZeroUsize::get));
+        let seed = optional_value::<u64>(&matches, "seed");
+        for preset in presets {
+            let mut params = preset.parameters().with_first_click(first_click);
+            if let Some(seed) = seed {
+                params = params.with_seed(seed);
+            }
+            for &name in &generators {
+                let report = simulate(params, games, threads, backend.as_ref(), || {
+                    named_generator(name, weight_map.as_ref(), max_attempts)
+                });
+                println!("{:<12} {:<9} {}", preset, name, report);
+            }
+        }
+        return;
+    }
+    if generators.len() > 1 {
+        Error::with_description(
+            "--generator can only be repeated with --simulate",
+            ErrorKind::ArgumentConflict,
+        )
+        .exit();
+    }
+
+    let mut params = Term::parameters((width, height), mines).with_first_click(first_click);
+    if let Some(seed) = optional_value::<u64>(&matches, "seed") {
+        params = params.with_seed(seed);
+    }
+
+    let mut generator = named_generator(
+        generators.first().copied().unwrap_or_default(),
+        weight_map.as_ref(),
+        max_attempts,
+    );
+
+    let min_3bv = optional_value::<usize>(&matches, "min-3bv");
+    let max_3bv = optional_value::<usize>(&matches, "max-3bv");
+    let required_rules: Vec<_> = matches
+        .values_of("require-rule")
+        .map(Iterator::collect)
+        .unwrap_or_default();
+    if min_3bv.is_some() || max_3bv.is_some() || !required_rules.is_empty() {
+        let mut band = DifficultyBand::new(min_3bv.unwrap_or(0)..=max_3bv.unwrap_or(usize::MAX));
+        for rule in Solver::default_rules() {
+            if required_rules.contains(&rule.short_name()) {
+                band = band.requiring(rule.as_ref());
+            }
+        }
+        let difficulty = DifficultyGenerator::new(generator, band);
+        generator = match max_attempts {
+            Some(max_attempts) => Box::new(difficulty.with_max_attempts(max_attempts)),
+            None => Box::new(difficulty),
+        };
+    }
+
+    if let Some(paths) = matches.values_of("board-file") {
+        let on_restart = match matches.value_of("on-restart") {
+            Some("replay") => OnRestart::Replay,
+            _ => OnRestart::Next,
+        };
+        let paths: Vec<_> = paths.collect();
+        let boards = BoardFileGenerator::load(&paths, on_restart)
+            .unwrap_or_else(|e| Error::with_description(&e.to_string(), ErrorKind::Io).exit());
+        generator = Box::new(boards);
+    }
+
+    if let Some(runs) = optional_value::<usize>(&matches, "check-quality") {
+        let click = Location::new(params.width / 2, params.height / 2);
+        let zone = ExclusionZone::for_first_click(&params, click);
+        match check_quality(generator.as_mut(), params, &zone, runs) {
+            Ok(report) => print!("{}", report),
+            Err(e) => Error::with_description(&e.to_string(), ErrorKind::InvalidValue).exit(),
+        }
+        return;
+    }
+
+    let mut term = Term::new(Minefield::with_generator(params, generator));
     term.go();
 }
+
+/// Plays the daily challenge and prints the shareable result code once the terminal is restored.
+fn play_daily(date: Date, other: Option<ResultCode>) {
+    let mut term = Term::new(Minefield::new(daily_parameters(date))).with_daily(date, other);
+    term.go();
+    let result = term.daily_result();
+    drop(term);
+
+    match result {
+        Some(result) => {
+            println!("Daily challenge {}: {}", date, result);
+            if let Some(other) = other {
+                println!("{}", result.compare(&other));
+            }
+        }
+        None => println!("Daily challenge {} not finished.", date),
+    }
+}
+
+/// Creates the generator selected with `--generator`, `improved` and `no-guess` place the mines
+/// with the weight map if there is one. `no-guess` gives up after `max_attempts` boards.
+fn named_generator(
+    name: &str,
+    weight_map: Option<&WeightMapGenerator>,
+    max_attempts: Option<usize>,
+) -> Box<dyn MinefieldGenerator> {
+    let uniform_or_weighted = || -> Box<dyn MinefieldGenerator> {
+        match weight_map {
+            Some(weight_map) => Box::new(weight_map.clone()),
+            None => Box::new(ImprovedGenerator),
+        }
+    };
+    match name {
+        "simple" => Box::new(SimpleGenerator),
+        "clustered" => Box::new(ClusteredGenerator::default()),
+        "gradient" => Box::new(GradientGenerator::default()),
+        "no-guess" => {
+            let generator = NoGuessGenerator::new(uniform_or_weighted());
+            match max_attempts {
+                Some(max_attempts) => Box::new(generator.with_max_attempts(max_attempts)),
+                None => Box::new(generator),
+            }
+        }
+        _ => uniform_or_weighted(),
+    }
+}
+
+fn named_first_click(name: &str) -> FirstClickPolicy {
+    match name {
+        "none" => FirstClickPolicy::NoGuarantee,
+        "safe-cell" => FirstClickPolicy::SafeCell,
+        "opening" => FirstClickPolicy::Opening,
+        "pre-opened" => FirstClickPolicy::PreOpened,
+        _ => unreachable!("First click policies are validated by clap."),
+    }
+}
+
+fn named_backend(name: &str) -> Box<dyn SolverBackend> {
+    match name {
+        "rules" => Box::new(RuleBackend),
+        "dpll" => Box::new(DpllBackend),
+        "gauss" => Box::new(GaussBackend),
+        _ => unreachable!("Backend names are validated by clap."),
+    }
+}
+
+/// Parses the value of argument `name` if it was given and exits with an error message if it is invalid.
+fn optional_value<T: FromStr>(matches: &ArgMatches, name: &str) -> Option<T> {
+    matches.value_of(name)?;
+    Some(value_t!(matches, name, T).unwrap_or_else(|e| e.exit()))
+}
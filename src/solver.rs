@@ -1,86 +1,144 @@
+// custom_debug_derive 0.5 expands its impls inside an anonymous const.
+#![allow(non_local_definitions)]
+
 use custom_debug_derive::Debug;
 
 use std::{
-    collections::{BTreeSet, HashSet},
+    collections::{BTreeMap, HashSet},
     fmt::Display,
     io::{LineWriter, Write},
     path::Path,
     str,
 };
 
-use crate::core::{Location, Minefield, State};
+use rayon::prelude::*;
+
+use crate::core::{Location, Minefield};
+
+mod benchmark;
+mod dpll;
+mod explain;
+mod fact_store;
+mod gauss;
+mod mine_count;
+mod oracle;
+mod probability;
+mod proximity;
+mod session;
+
+pub use benchmark::*;
+pub use dpll::*;
+pub use explain::*;
+pub use fact_store::*;
+pub use gauss::*;
+pub use mine_count::*;
+pub use oracle::*;
+pub use probability::*;
+pub use proximity::*;
+pub use session::*;
+
+/// Finds the hidden cells that are guaranteed to be safe and those guaranteed to be mines.
+///
+/// The backends trade speed for completeness, see [`RuleBackend`] and [`DpllBackend`].
+pub trait SolverBackend: Sync {
+    fn name(&self) -> &'static str;
+    /// Returns the locations that are guaranteed to be safe and those guaranteed to be mines.
+    fn solve(&self, mf: &Minefield) -> (HashSet<Location>, HashSet<Location>);
+
+    /// Returns a solver for the positions of one game. Backends that can reuse work from the
+    /// last position override this, the default solves every position from scratch.
+    fn session(&self) -> Session<'_> {
+        Box::new(move |mf| self.solve(mf))
+    }
+}
+
+/// Solves the successive positions of one game, see [`SolverBackend::session`].
+pub type Session<'a> = Box<dyn FnMut(&Minefield) -> (HashSet<Location>, HashSet<Location>) + 'a>;
+
+/// Applies the default [`Rule`]s of the [`Solver`]. They are fast but only capture
+/// common patterns, so some forced cells are missed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RuleBackend;
+
+impl SolverBackend for RuleBackend {
+    fn name(&self) -> &'static str {
+        "rules"
+    }
+
+    fn solve(&self, mf: &Minefield) -> (HashSet<Location>, HashSet<Location>) {
+        Solver::solve(mf)
+    }
+
+    fn session(&self) -> Session<'_> {
+        let mut session = SolverSession::new();
+        Box::new(move |mf| {
+            let analysis = session.update(mf);
+            (analysis.safe, analysis.mines)
+        })
+    }
+}
 
-trait Rule: std::fmt::Debug {
-    fn derive(&self, repo: &Solver) -> Vec<Fact>;
+/// A rule derives new facts from the facts of the previous iteration.
+///
+/// The solver applies the rules in parallel, each rule to chunks of the previous iteration's
+/// facts, so rules must be shareable between threads.
+pub trait Rule: std::fmt::Debug + Send + Sync {
+    fn derive(&self, repo: &RuleInput) -> Vec<Fact>;
     fn name(&self) -> &'static str {
         std::any::type_name::<Self>()
     }
+    /// Returns the name without the module path, as used on the command line.
+    fn short_name(&self) -> &'static str {
+        short_name(self.name())
+    }
+}
+
+/// Strips the module path from a rule name, e.g. one stored in [`FactDebug::produced_by`].
+pub(crate) fn short_name(name: &'static str) -> &'static str {
+    name.rsplit("::").next().unwrap_or(name)
 }
 
 /// If a set of N location has at least N mines, it has exactly N mines.
 #[derive(Debug)]
-struct MinAllToExact;
+pub struct MinAllToExact;
 
 impl Rule for MinAllToExact {
-    fn derive(&self, repo: &Solver) -> Vec<Fact> {
+    fn derive(&self, repo: &RuleInput) -> Vec<Fact> {
         repo.iter_previous_iteration()
             .filter(|f| f.is_min() && f.cardinality() == f.count)
-            .map(|f| f.derive_kind(Constraint::Exact, repo.iteration, self, f))
+            .map(|f| f.derive_kind(Constraint::Exact, repo, self, f))
             .collect()
     }
 }
 
 /// If a set of location has at most 0 mines, it has exactly 0 mines.
 #[derive(Debug)]
-struct MaxZeroToExact;
+pub struct MaxZeroToExact;
 
 impl Rule for MaxZeroToExact {
-    fn derive(&self, repo: &Solver) -> Vec<Fact> {
+    fn derive(&self, repo: &RuleInput) -> Vec<Fact> {
         repo.iter_previous_iteration()
             .filter(|f| f.is_max() && f.count == 0)
-            .map(|f| f.derive_kind(Constraint::Exact, repo.iteration, self, f))
+            .map(|f| f.derive_kind(Constraint::Exact, repo, self, f))
             .collect()
     }
 }
-/// If a set of locations has exactly N mines, it has at least N mines.
-#[derive(Debug)]
-struct ExactToMin;
 
-impl Rule for ExactToMin {
-    fn derive(&self, repo: &Solver) -> Vec<Fact> {
-        repo.iter_previous_iteration()
-            .filter(|f| f.is_exact())
-            .map(|f| f.derive_kind(Constraint::Min, repo.iteration, self, f))
-            .collect()
-    }
-}
-
-/// If a set of locations has exactly N mines, it has at most N mines.
-#[derive(Debug)]
-struct ExactToMax;
-
-impl Rule for ExactToMax {
-    fn derive(&self, repo: &Solver) -> Vec<Fact> {
-        repo.iter_previous_iteration()
-            .filter(|f| f.is_exact())
-            .map(|f| f.derive_kind(Constraint::Max, repo.iteration, self, f))
-            .collect()
-    }
+/// Orders a pair of facts as (min, max) in the ways it can be combined. An exact fact is a min
+/// and a max fact at once, so a pair of exact facts is combined both ways.
+fn as_min_and_max<'a>((l, r): (&'a Fact, &'a Fact)) -> impl Iterator<Item = (&'a Fact, &'a Fact)> {
+    IntoIterator::into_iter([(l, r), (r, l)]).filter(|(min, max)| !min.is_max() && !max.is_min())
 }
 
 /// If a min proximity is a true subset of a max proximity and the max proximity has more or equal number of mines,
 /// then the remaining proximity max without min has at most the remaining mines of max - min.
 #[derive(Debug)]
-struct MinWithinMaxCombinator;
+pub struct MinWithinMaxCombinator;
 
 impl Rule for MinWithinMaxCombinator {
-    fn derive(&self, repo: &Solver) -> Vec<Fact> {
+    fn derive(&self, repo: &RuleInput) -> Vec<Fact> {
         repo.iter_new_with_old()
-            .filter_map(|(l, r)| match (l.kind, r.kind) {
-                (Constraint::Min, Constraint::Max) => Some((l, r)),
-                (Constraint::Max, Constraint::Min) => Some((r, l)),
-                _ => None,
-            })
+            .flat_map(as_min_and_max)
             .filter(|(min, max)| {
                 max.count >= min.count
                     && min.proximity.len() < max.proximity.len()
@@ -92,7 +150,7 @@ impl Rule for MinWithinMaxCombinator {
                     max.count - min.count,
                     &max.proximity - &min.proximity,
                     repo.iteration,
-                    FactDebug::derived_two(self, min, max),
+                    FactDebug::derived_two(self, min, max, repo.track_derivations),
                 )
             })
             .collect()
@@ -102,25 +160,21 @@ impl Rule for MinWithinMaxCombinator {
 /// If a max proximity is intersecting a min proximity and the min proximity has more or equal number of mines,
 /// then the remaining proximity min without max has at least the remaining mines of min - max.
 #[derive(Debug)]
-struct MaxIntersectsMinCombinator;
+pub struct MaxIntersectsMinCombinator;
 
 impl Rule for MaxIntersectsMinCombinator {
-    fn derive(&self, repo: &Solver) -> Vec<Fact> {
+    fn derive(&self, repo: &RuleInput) -> Vec<Fact> {
         repo.iter_new_with_old()
-            .filter_map(|(l, r)| match (l.kind, r.kind) {
-                (Constraint::Min, Constraint::Max) => Some((l, r)),
-                (Constraint::Max, Constraint::Min) => Some((r, l)),
-                _ => None,
-            })
+            .flat_map(as_min_and_max)
             .filter_map(|(min, max)| {
-                let intersection = &min.proximity & &max.proximity;
-                if intersection.is_empty() {
+                let intersection = min.proximity.intersection_len(&max.proximity);
+                if intersection == 0 {
                     // if min is disjoint to max, the two fact do not overlap and
                     // therefore nothing can be derived.
                     return None;
                 }
 
-                let max_mines_in_intersection = max.count.min(intersection.len());
+                let max_mines_in_intersection = max.count.min(intersection);
                 if min.count <= max_mines_in_intersection {
                     // if min has less mines in total than maximum in intersection, all mines
                     // could be in intersection and therefore no meaningful fact can be derived.
@@ -132,7 +186,7 @@ impl Rule for MaxIntersectsMinCombinator {
                     min.count - max_mines_in_intersection,
                     &min.proximity - &max.proximity,
                     repo.iteration,
-                    FactDebug::derived_two(self, min, max),
+                    FactDebug::derived_two(self, min, max, repo.track_derivations),
                 ))
             })
             .collect()
@@ -140,16 +194,16 @@ impl Rule for MaxIntersectsMinCombinator {
 }
 
 #[derive(Debug)]
-struct Seeder;
+pub struct Seeder;
 
 impl Rule for Seeder {
-    fn derive(&self, _: &Solver) -> Vec<Fact> {
+    fn derive(&self, _: &RuleInput) -> Vec<Fact> {
         vec![]
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum Constraint {
+pub enum Constraint {
     Min,
     Exact,
     Max,
@@ -174,24 +228,12 @@ fn opt_fmt<T: std::fmt::Display>(t: &Option<T>, f: &mut std::fmt::Formatter) ->
     }
 }
 
-fn set_fmt<S, T>(ts: &S, f: &mut std::fmt::Formatter) -> std::fmt::Result
-where
-    for<'a> &'a S: IntoIterator<Item = &'a T>,
-    T: std::fmt::Display,
-{
-    write!(f, "{{ ")?;
-    for t in ts.into_iter() {
-        write!(f, "{}, ", t)?;
-    }
-    write!(f, "}} ")
-}
-
 #[derive(Debug, Clone)]
-struct FactDebug {
+pub struct FactDebug {
     #[debug(with = "opt_fmt")]
     pub base_location: Option<Location>,
     pub produced_by: &'static str,
-    #[cfg(feature = "derived_from")]
+    /// The facts this fact was derived from, empty unless the solver tracks derivations.
     pub derived_from: Vec<Fact>,
 }
 
@@ -200,36 +242,43 @@ impl FactDebug {
         Self {
             base_location: base_location.into(),
             produced_by: produced_by.name(),
-            #[cfg(feature = "derived_from")]
             derived_from: Vec::new(),
         }
     }
 
-    fn derived_one(produced_by: &dyn Rule, _parent_fact: &Fact) -> Self {
-        Self {
-            base_location: None,
-            produced_by: produced_by.name(),
-            #[cfg(feature = "derived_from")]
-            derived_from: vec![_parent_fact.clone()],
-        }
+    fn derived_one(produced_by: &dyn Rule, parent_fact: &Fact, track: bool) -> Self {
+        Self::derived(produced_by, track, || vec![parent_fact.clone()])
+    }
+
+    fn derived_two(
+        produced_by: &dyn Rule,
+        parent_fact_1: &Fact,
+        parent_fact_2: &Fact,
+        track: bool,
+    ) -> Self {
+        Self::derived(produced_by, track, || {
+            vec![parent_fact_1.clone(), parent_fact_2.clone()]
+        })
     }
 
-    fn derived_two(produced_by: &dyn Rule, _parent_fact_1: &Fact, _parent_fact_2: &Fact) -> Self {
+    /// Cloning the parents copies their whole derivation, so it is only done if `track` is set.
+    fn derived<P: FnOnce() -> Vec<Fact>>(produced_by: &dyn Rule, track: bool, parents: P) -> Self {
         Self {
             base_location: None,
             produced_by: produced_by.name(),
-            #[cfg(feature = "derived_from")]
-            derived_from: vec![_parent_fact_1.clone(), _parent_fact_2.clone()],
+            derived_from: match track {
+                true => parents(),
+                false => Vec::new(),
+            },
         }
     }
 }
 
 #[derive(Debug, Clone)]
-struct Fact {
+pub struct Fact {
     pub kind: Constraint,
     pub count: usize,
-    #[debug(with = "set_fmt")]
-    pub proximity: BTreeSet<Location>,
+    pub proximity: Proximity,
     pub iteration: usize,
     pub debug: FactDebug,
 }
@@ -275,7 +324,7 @@ impl Fact {
     fn new(
         kind: Constraint,
         count: usize,
-        proximity: BTreeSet<Location>,
+        proximity: Proximity,
         iteration: usize,
         debug: FactDebug,
     ) -> Self {
@@ -288,7 +337,7 @@ impl Fact {
         }
     }
 
-    fn seeded<L>(count: usize, proximity: BTreeSet<Location>, base_location: L) -> Self
+    fn seeded<L>(count: usize, proximity: Proximity, base_location: L) -> Self
     where
         L: Into<Option<Location>>,
     {
@@ -304,7 +353,7 @@ impl Fact {
     fn derive_kind(
         &self,
         kind: Constraint,
-        iteration: usize,
+        repo: &RuleInput,
         produced_by: &dyn Rule,
         parent_fact: &Fact,
     ) -> Self {
@@ -312,8 +361,8 @@ impl Fact {
             kind,
             count: self.count,
             proximity: self.proximity.clone(),
-            iteration,
-            debug: FactDebug::derived_one(produced_by, parent_fact),
+            iteration: repo.iteration,
+            debug: FactDebug::derived_one(produced_by, parent_fact, repo.track_derivations),
         }
     }
 
@@ -333,13 +382,28 @@ impl Fact {
         matches!(self.kind, Constraint::Exact)
     }
 
+    /// Whether `other` is about the same locations and follows from this fact, so that it
+    /// cannot lead to anything this fact does not lead to.
+    ///
+    /// An exact fact also subsumes the min and max facts with the same count, the combinators
+    /// use it as both.
+    fn subsumes(&self, other: &Fact) -> bool {
+        use Constraint::*;
+        self.proximity == other.proximity
+            && match (self.kind, other.kind) {
+                (Exact, Exact) => self.count == other.count,
+                (Max, Max) => self.count <= other.count,
+                (Min, Min) => self.count >= other.count,
+                (Exact, Max) => self.count <= other.count,
+                (Exact, Min) => self.count >= other.count,
+                _ => false,
+            }
+    }
+
     fn serialize(&self) -> String {
         let proximity = {
             let mut iter = self.proximity.iter();
-            let first = iter
-                .next()
-                .map(ToString::to_string)
-                .unwrap_or_else(Default::default);
+            let first = iter.next().map(|l| l.to_string()).unwrap_or_default();
             iter.fold(first, |mut elements, x| {
                 elements.push(',');
                 elements.push_str(&x.to_string());
@@ -358,19 +422,134 @@ impl Fact {
         );
 
         #[cfg(feature = "derived_from")]
+        let default = format!("{};{:?}", default, self.debug.derived_from);
+
+        default
+    }
+}
+
+/// The fact about all remaining locations is only seeded up to this many locations.
+///
+/// Every other fact can be subtracted from it and the differences combine again, so a fact about
+/// `n` locations can lead to facts about any of its `2^n` subsets. Up to 10 locations, i.e. at
+/// most 1024 subsets, this stays cheap, while the fact about all hidden cells of a board made
+/// the number of facts explode in the endgame. Larger remaining areas are bounded by the mine
+/// count without this fact, see [`Solver::seed_mine_count_facts`].
+const MAX_REMAINING_FACT_CARDINALITY: usize = 10;
+
+/// Number of facts of the previous iteration a rule is applied to in one parallel task. It is
+/// fixed, so the chunks and the order of the derived facts do not depend on the thread count.
+const RULE_CHUNK_SIZE: usize = 32;
+
+/// What the solver found out about a mine field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Analysis {
+    /// Hidden locations that are guaranteed to be safe.
+    pub safe: HashSet<Location>,
+    /// Hidden locations that are guaranteed to be mines.
+    pub mines: HashSet<Location>,
+    /// Number of facts produced by each rule, keyed by [`Rule::name`].
+    /// Facts seeded from the mine field are counted for [`Seeder`].
+    pub facts_per_rule: BTreeMap<&'static str, usize>,
+    /// Number of times the rules were applied until no new facts were found.
+    pub iterations: usize,
+}
+
+/// Analyzes the mine field with the default rules without printing anything.
+pub fn analyze(mf: &Minefield) -> Analysis {
+    Solver::analyze_with_rules(mf, Solver::default_rules())
+}
+
+/// Applies the rules to the facts seeded in `iteration` and the facts derived from them until
+/// nothing changes anymore. Returns the last iteration.
+fn saturate(
+    rules: &[Box<dyn Rule>],
+    facts: &mut FactStore,
+    mut iteration: usize,
+    track_derivations: bool,
+) -> usize {
+    let mut repeat = true;
+    while repeat {
+        iteration += 1;
+        repeat = false;
+        for fact in derive_in_parallel(rules, facts, iteration, track_derivations)
+            .into_iter()
+            .flatten()
         {
-            return format!("{};{:?}", default, self.debug.derived_from);
+            repeat |= facts.insert(fact);
         }
+    }
+    iteration
+}
 
-        default
+/// Applies every rule to every chunk of the previous iteration's facts on the rayon thread
+/// pool. The derived facts are returned in the order of the rules and chunks, which is the
+/// order a serial run derives them in, so the facts do not depend on the number of threads.
+fn derive_in_parallel(
+    rules: &[Box<dyn Rule>],
+    facts: &FactStore,
+    iteration: usize,
+    track_derivations: bool,
+) -> Vec<Vec<Fact>> {
+    let previous: Vec<_> = facts.iteration(iteration - 1).collect();
+    let work: Vec<_> = rules
+        .iter()
+        .flat_map(|rule| {
+            previous
+                .chunks(RULE_CHUNK_SIZE)
+                .map(move |chunk| (rule, chunk))
+        })
+        .collect();
+    work.into_par_iter()
+        .map(|(rule, previous)| {
+            rule.derive(&RuleInput {
+                facts,
+                previous,
+                iteration,
+                track_derivations,
+            })
+        })
+        .collect()
+}
+
+/// What a [`Rule`] derives facts from: a chunk of the facts of the previous iteration and all
+/// stored facts.
+pub struct RuleInput<'a> {
+    facts: &'a FactStore,
+    previous: &'a [&'a Fact],
+    /// The iteration the derived facts belong to.
+    pub iteration: usize,
+    /// Whether the derived facts keep the facts they were derived from.
+    pub track_derivations: bool,
+}
+
+impl<'a> RuleInput<'a> {
+    /// Returns the facts of the previous iteration in this chunk.
+    pub fn iter_previous_iteration(&self) -> impl Iterator<Item = &'a Fact> + '_ {
+        self.previous.iter().copied()
+    }
+
+    /// Pairs the facts of the previous iteration in this chunk with the facts they share a
+    /// location with. Facts about disjoint locations cannot be combined.
+    pub fn iter_new_with_old(&self) -> impl Iterator<Item = (&'a Fact, &'a Fact)> + '_ {
+        let facts = self.facts;
+        self.iter_previous_iteration()
+            .flat_map(move |l| facts.overlapping(l).map(move |r| (l, r)))
     }
 }
 
 #[derive(Debug)]
-struct Solver<'mf> {
-    facts: HashSet<Fact>,
+pub struct Solver<'mf> {
+    facts: FactStore,
     iteration: usize,
     rules: Vec<Box<dyn Rule>>,
+    /// Whether derived facts keep their parents in [`FactDebug::derived_from`]. This makes
+    /// deducing several times slower, so it is only set for explanations unless the
+    /// `derived_from` feature tracks every run.
+    track_derivations: bool,
+    /// Receives the fact database while deducing, diagnostics are only written if set.
+    #[debug(skip)]
+    trace: Option<Box<dyn Write + 'mf>>,
     #[debug(skip)]
     mine_field: &'mf Minefield,
 }
@@ -378,9 +557,11 @@ struct Solver<'mf> {
 impl<'mf> Solver<'mf> {
     fn new(mine_field: &'mf Minefield) -> Self {
         Self {
-            facts: HashSet::new(),
+            facts: FactStore::default(),
             iteration: 0,
             rules: Vec::new(),
+            track_derivations: cfg!(feature = "derived_from"),
+            trace: None,
             mine_field,
         }
     }
@@ -400,57 +581,104 @@ impl<'mf> Solver<'mf> {
         self.facts.insert(universal_fact);
     }
 
+    /// Returns the fact that all locations which are not known yet contain exactly the mines
+    /// which are not known yet. Leaving out the known locations keeps the fact small, otherwise
+    /// combining it with all other facts quickly produces exponentially many facts.
+    fn remaining_fact(&self) -> Option<Fact> {
+        let known_mines = self.guaranteed_mines();
+        let known_safe_locations = self.guaranteed_safe_locations();
+        let remaining_fact = Fact::seeded(
+            self.mine_field.mine_count() - known_mines.len(),
+            self.mine_field
+                .fog()
+                .loc_iter()
+                .filter(|(_, s)| s.is_hidden() || s.is_marked())
+                .map(|(l, _)| l)
+                .filter(|l| !known_mines.contains(l) && !known_safe_locations.contains(l))
+                .collect(),
+            None,
+        );
+        if remaining_fact.cardinality() > MAX_REMAINING_FACT_CARDINALITY {
+            return None;
+        }
+        Some(Fact {
+            iteration: self.iteration,
+            ..remaining_fact
+        })
+    }
+
     fn seed(&mut self) {
-        let fog = self.mine_field.fog();
+        let locations: Vec<_> = self.mine_field.fog().loc_iter().map(|(l, _)| l).collect();
+        self.seed_numbers(locations);
+    }
+
+    /// Seeds the facts of the revealed numbers among `locations` in the current iteration.
+    ///
+    /// Marked cells count as unknown like hidden ones. A mark is the player's guess, so a wrong
+    /// mark must not make the solver report wrong safe cells.
+    fn seed_numbers<I: IntoIterator<Item = Location>>(&mut self, locations: I) {
+        let mine_field = self.mine_field;
+        let fog = mine_field.fog();
         let make_proximity = |l: Location| {
             l.neighbours()
-                .filter(|&l| fog.get(l).map(State::is_hidden).unwrap_or(false))
+                .filter(|&l| {
+                    fog.get(l)
+                        .map(|s| s.is_hidden() || s.is_marked())
+                        .unwrap_or(false)
+                })
                 .collect()
         };
 
-        self.facts.extend(
-            fog.loc_iter()
-                .filter_map(|(l, s)| Some((l, *s.as_revealed()?)))
-                .map(|(l, s)| Fact::seeded(s, make_proximity(l), l)),
+        let iteration = self.iteration;
+        self.add(
+            locations
+                .into_iter()
+                .filter_map(|l| Some((l, *fog.get(l)?.as_revealed()?)))
+                .map(|(l, s)| Fact {
+                    iteration,
+                    ..Fact::seeded(s, make_proximity(l), l)
+                }),
         );
     }
 
-    fn seed_rules(&mut self) {
-        self.rules.push(Box::new(MinAllToExact));
-        self.rules.push(Box::new(MaxZeroToExact));
-        self.rules.push(Box::new(ExactToMin));
-        self.rules.push(Box::new(ExactToMax));
-        self.rules.push(Box::new(MinWithinMaxCombinator));
-        self.rules.push(Box::new(MaxIntersectsMinCombinator));
+    /// Removes all facts about any of the `locations`.
+    fn retract(&mut self, locations: &HashSet<Location>) {
+        self.facts
+            .retain(|f| !f.proximity.iter().any(|l| locations.contains(&l)));
     }
 
-    fn iter(&self) -> impl Iterator<Item = &Fact> {
-        self.facts.iter()
+    /// Returns all rules the solver uses by default.
+    pub fn default_rules() -> Vec<Box<dyn Rule>> {
+        vec![
+            Box::new(MinAllToExact),
+            Box::new(MaxZeroToExact),
+            Box::new(MinWithinMaxCombinator),
+            Box::new(MaxIntersectsMinCombinator),
+        ]
     }
 
-    fn iter_previous_iteration(&self) -> impl Iterator<Item = &Fact> {
-        let previous_iteration = self.iteration - 1;
-        self.facts
-            .iter()
-            .filter(move |f| f.iteration == previous_iteration)
+    fn seed_rules(&mut self) {
+        self.rules.extend(Self::default_rules());
     }
 
-    fn iter_new_with_old(&self) -> impl Iterator<Item = (&Fact, &Fact)> {
-        self.iter_previous_iteration()
-            .flat_map(move |l| self.facts.iter().map(move |r| (l, r)))
+    fn iter(&self) -> impl Iterator<Item = &Fact> {
+        self.facts.iter()
     }
 
+    /// Adds the facts and returns whether any of them was new and not subsumed.
     fn add<I: IntoIterator<Item = Fact>>(&mut self, container: I) -> bool {
-        let count = self.facts.len();
-        self.facts.extend(container);
-        self.facts.len() > count
+        let mut added = false;
+        for fact in container {
+            added |= self.facts.insert(fact);
+        }
+        added
     }
 
     fn guaranteed_safe_locations(&self) -> HashSet<Location> {
         self.facts
             .iter()
             .filter(|f| f.is_exact() && f.count == 0)
-            .flat_map(|f| f.proximity.iter().copied())
+            .flat_map(|f| f.proximity.iter())
             .collect()
     }
 
@@ -458,93 +686,285 @@ impl<'mf> Solver<'mf> {
         self.facts
             .iter()
             .filter(|f| f.is_exact() && f.count == f.proximity.len())
-            .flat_map(|f| f.proximity.iter().copied())
+            .flat_map(|f| f.proximity.iter())
             .collect()
     }
 
-    fn solve(mf: &Minefield) -> (HashSet<Location>, HashSet<Location>) {
-        Solver::solve_dump(mf, None)
+    /// Returns the locations that are guaranteed to be safe and those guaranteed to be mines.
+    pub fn solve(mf: &Minefield) -> (HashSet<Location>, HashSet<Location>) {
+        Solver::solve_with_rules(mf, Self::default_rules())
     }
 
+    /// Like [`Solver::solve`] but only derives facts with the given rules.
+    pub fn solve_with_rules(
+        mf: &Minefield,
+        rules: Vec<Box<dyn Rule>>,
+    ) -> (HashSet<Location>, HashSet<Location>) {
+        let analysis = Solver::analyze_with_rules(mf, rules);
+        (analysis.safe, analysis.mines)
+    }
+
+    /// Like [`analyze`] but only derives facts with the given rules.
+    pub fn analyze_with_rules(mf: &Minefield, rules: Vec<Box<dyn Rule>>) -> Analysis {
+        let mut solver = Solver::new(mf);
+        solver.rules = rules;
+        solver.deduce();
+        solver.analysis()
+    }
+
+    /// Like [`Solver::analyze_with_rules`] but writes the fact database and the number of facts
+    /// per rule after every iteration to `trace`.
+    ///
+    /// # Panics
+    /// Panics if writing to `trace` fails.
+    pub fn analyze_traced(
+        mf: &Minefield,
+        rules: Vec<Box<dyn Rule>>,
+        trace: &mut dyn Write,
+    ) -> Analysis {
+        let mut solver = Solver::new(mf);
+        solver.rules = rules;
+        solver.trace = Some(Box::new(trace));
+        solver.deduce();
+        solver.analysis()
+    }
+
+    /// Solves with the given rules and writes all facts to `path`, as a Graphviz DOT graph if it
+    /// ends with `.dot` and as semicolon separated values otherwise. The derivations are
+    /// tracked, so the graph shows which facts each fact was derived from.
+    pub fn dump_facts(
+        mf: &Minefield,
+        rules: Vec<Box<dyn Rule>>,
+        path: &Path,
+    ) -> std::io::Result<()> {
+        let mut solver = Solver::new(mf);
+        solver.rules = rules;
+        solver.track_derivations = true;
+        solver.deduce();
+        solver.dump(path)
+    }
+
+    fn analysis(&self) -> Analysis {
+        Analysis {
+            safe: self.guaranteed_safe_locations(),
+            mines: self.guaranteed_mines(),
+            facts_per_rule: self.facts_per_rule(),
+            iterations: self.iteration,
+        }
+    }
+
+    fn facts_per_rule(&self) -> BTreeMap<&'static str, usize> {
+        self.facts
+            .iter()
+            .fold(BTreeMap::new(), |mut facts_per_rule, fact| {
+                *facts_per_rule.entry(fact.debug.produced_by).or_default() += 1;
+                facts_per_rule
+            })
+    }
+
+    /// Writes to the trace sink, if there is one.
+    fn trace<F: FnOnce(&Self) -> String>(&mut self, message: F) {
+        if self.trace.is_none() {
+            return;
+        }
+        let message = message(self);
+        if let Some(trace) = self.trace.as_mut() {
+            writeln!(trace, "{}", message).expect("Failed to write solver trace.");
+        }
+    }
+
+    /// Applies the rules until nothing changes anymore.
+    ///
+    /// The facts are split into independent components that are deduced in parallel and then
+    /// merged. Only a fact about all remaining locations connects the components, see
+    /// [`FactStore::into_components`]. A traced run deduces all facts together, so the trace
+    /// shows every iteration of the whole fact database.
     fn run(&mut self) {
+        if self.trace.is_some() {
+            self.run_traced();
+            return;
+        }
+
+        let (rules, iteration, track) = (&self.rules, self.iteration, self.track_derivations);
+        let components: Vec<_> = std::mem::take(&mut self.facts)
+            .into_components()
+            .into_par_iter()
+            .map(|mut facts| {
+                let last_iteration = saturate(rules, &mut facts, iteration, track);
+                (facts, last_iteration)
+            })
+            .collect();
+
+        self.iteration = components
+            .iter()
+            .map(|(_, last_iteration)| *last_iteration)
+            .max()
+            .unwrap_or(iteration + 1);
+        for (facts, _) in components {
+            self.facts.append(facts);
+        }
+    }
+
+    fn run_traced(&mut self) {
         let mut repeat = true;
         while repeat {
-            self.print_fact_stats();
+            self.trace(|s| {
+                format!(
+                    "{} facts after iteration {}.\n{:#?}",
+                    s.facts.len(),
+                    s.iteration,
+                    s.facts_per_rule()
+                )
+            });
             self.iteration += 1;
-            #[allow(clippy::needless_collect)]
-            // false positive, cannot remove collect or exclusive borrow overlaps shared borrow of self
-            let new_facts: Vec<_> = self.rules.iter().map(|r| r.derive(self)).collect();
+            let new_facts = derive_in_parallel(
+                &self.rules,
+                &self.facts,
+                self.iteration,
+                self.track_derivations,
+            );
             repeat = self.add(new_facts.into_iter().flatten());
         }
     }
 
+    /// Seeds the facts from the mine field and applies the rules until nothing changes anymore.
+    fn deduce(&mut self) {
+        self.seed();
+        self.trace(|s| format!("Base Facts: {:#?}", s));
+        self.deduce_seeded();
+    }
+
+    /// Applies the rules to the facts seeded in the current iteration until nothing changes
+    /// anymore. Older facts are only combined with newer ones.
+    fn deduce_seeded(&mut self) {
+        self.run();
+        // The mine count connects all facts, so it is only used once the rules are done.
+        while self.seed_mine_count_facts() {
+            self.run();
+        }
+
+        self.trace(|s| format!("Final Facts: {:#?}", s));
+    }
+
+    /// Solves with the default rules, prints the trace and optionally dumps the facts to a file.
     fn solve_dump(
         mf: &Minefield,
         dump_path: Option<&Path>,
     ) -> (HashSet<Location>, HashSet<Location>) {
+        let mut trace = Vec::new();
         let mut solver = Solver::new(mf);
+        solver.trace = Some(Box::new(&mut trace));
         solver.seed_rules();
-        solver.seed();
-        println!("Base Facts: {:#?}", solver);
-
-        solver.run();
-
-        let remaining_mines = solver.mine_field.mine_count() - solver.guaranteed_mines().len();
-        if solver.mine_field.unobserved_count() <= remaining_mines {
-            // skip one iteration to mark adding a fact
-            solver.seed_universal_fact();
-            solver.run();
-        }
+        solver.deduce();
 
-        println!("Final Facts: {:#?}", solver);
         if let Some(path) = dump_path {
             solver.dump(path).expect("Failed to dump facts to file.");
         }
 
         let safe_locations = solver.guaranteed_safe_locations();
         let mines = solver.guaranteed_mines();
+        drop(solver);
+        // print instead of writing to stdout directly, so the test harness captures it
+        print!("{}", String::from_utf8_lossy(&trace));
         (safe_locations, mines)
     }
 
-    fn print_fact_stats(&self) {
-        use std::collections::HashMap;
-        println!(
-            "{} facts after iteration {}.",
-            self.facts.len(),
-            self.iteration
-        );
-
-        let facts_per_rule: HashMap<&str, usize> =
-            self.facts
-                .iter()
-                .fold(HashMap::new(), |mut facts_per_rule, fact| {
-                    *facts_per_rule.entry(fact.debug.produced_by).or_default() += 1;
-                    facts_per_rule
-                });
-
-        println!("{:#?}", facts_per_rule);
-    }
-
+    /// Writes all facts to `path`, as a Graphviz DOT graph if it ends with `.dot`
+    /// and as semicolon separated values otherwise.
     fn dump(&self, path: &Path) -> std::io::Result<()> {
         let file = std::fs::File::create(path)?;
         let mut writer = LineWriter::new(file);
+        if path.extension().is_some_and(|e| e == "dot") {
+            return self.write_dot(&mut writer);
+        }
         writeln!(
             writer,
             "base location;produced by;iteration;kind;count;proximity;predecessors"
         )?;
-        for fact in &self.facts {
+        for fact in self.facts.iter() {
             let line = fact.serialize();
             writeln!(writer, "{}", line)?;
         }
         Ok(())
     }
+
+    /// Writes the facts as a DOT graph with one node per fact, coloured by the rule that
+    /// produced it. If derivations are tracked, each fact has edges to the stored facts it was
+    /// derived from.
+    fn write_dot(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        let rules: Vec<_> = IntoIterator::into_iter([Seeder.name(), MineCount.name()])
+            .chain(self.rules.iter().map(|r| r.name()))
+            .collect();
+        let colour = |rule: &str| {
+            let index = rules.iter().position(|&r| r == rule).unwrap_or(rules.len());
+            RULE_COLOURS[index % RULE_COLOURS.len()]
+        };
+
+        let mut facts: Vec<_> = self.facts.iter().collect();
+        facts.sort_unstable_by_key(|&f| (f.iteration, f));
+        let ids: std::collections::HashMap<&Fact, usize> =
+            facts.iter().enumerate().map(|(i, &f)| (f, i)).collect();
+
+        writeln!(writer, "digraph facts {{")?;
+        writeln!(writer, "    rankdir=BT;")?;
+        writeln!(writer, "    node [shape=box, style=filled];")?;
+        for (id, fact) in facts.iter().enumerate() {
+            let proximity: Vec<_> = fact.proximity.iter().map(|l| l.to_string()).collect();
+            writeln!(
+                writer,
+                "    f{} [label=\"{} {}\\n{{{}}}\", fillcolor={}, tooltip=\"{} in iteration {}\"];",
+                id,
+                fact.kind,
+                fact.count,
+                proximity.join(", "),
+                colour(fact.debug.produced_by),
+                short_name(fact.debug.produced_by),
+                fact.iteration
+            )?;
+            let mut parents: Vec<_> = fact.debug.derived_from.iter().collect();
+            while let Some(parent) = parents.pop() {
+                match ids.get(parent) {
+                    Some(parent_id) => writeln!(writer, "    f{} -> f{};", id, parent_id)?,
+                    // e.g. a max fact that the exact fact derived from it replaced
+                    None => parents.extend(&parent.debug.derived_from),
+                }
+            }
+        }
+
+        writeln!(writer, "    subgraph cluster_legend {{")?;
+        writeln!(writer, "        label=\"Rules\";")?;
+        for (i, &rule) in rules.iter().enumerate() {
+            writeln!(
+                writer,
+                "        rule{} [label=\"{}\", fillcolor={}];",
+                i,
+                short_name(rule),
+                colour(rule)
+            )?;
+        }
+        writeln!(writer, "    }}")?;
+        writeln!(writer, "}}")
+    }
 }
 
+/// Fill colours of the facts in DOT graphs, assigned to the seeder and the rules in order.
+const RULE_COLOURS: &[&str] = &[
+    "lightgrey",
+    "lightblue",
+    "palegreen",
+    "khaki",
+    "lightsalmon",
+    "plum",
+    "lightpink",
+    "lightcyan",
+];
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    use crate::core::{Action, PendingCommand};
 
     #[test]
     fn seed() {
@@ -555,7 +975,7 @@ mod tests {
                          eeee3mm2
                          1m2m223m
                         ";
-        let mf = Minefield::new_active_game(&grid);
+        let mf = Minefield::new_active_game(grid);
         let mut repo = Solver::new(&mf);
         repo.seed();
         repo.seed_universal_fact();
@@ -628,14 +1048,14 @@ mod tests {
             ),
         ];
 
-        let actual = repo.facts.into_iter().collect();
+        let actual = repo.facts.iter().cloned().collect();
         check_facts(expected, actual);
     }
 
     #[test]
     fn one_fact_mine_deduction() {
         let grid = "m1";
-        let mf = Minefield::new_active_game(&grid);
+        let mf = Minefield::new_active_game(grid);
 
         let (safe, mine) = Solver::solve_dump(&mf, dump_facts_path().as_deref());
 
@@ -643,12 +1063,80 @@ mod tests {
         assert_eq!(locations([]), safe);
     }
 
+    #[test]
+    fn analysis_reports_rule_statistics() {
+        let mf = Minefield::new_active_game("m1\ne1\nee");
+
+        let analysis = analyze(&mf);
+        assert_eq!(locations([(0, 2), (1, 2)]), analysis.safe);
+        assert_eq!(locations([]), analysis.mines);
+        assert!(analysis.iterations > 1);
+        assert_eq!(analysis.facts_per_rule[Seeder.name()], 2);
+        assert!(analysis.facts_per_rule.len() > 1);
+
+        let mut trace = Vec::new();
+        let traced = Solver::analyze_traced(&mf, Solver::default_rules(), &mut trace);
+        assert_eq!(analysis, traced);
+        let trace = String::from_utf8(trace).unwrap();
+        assert!(trace.starts_with("Base Facts"));
+        assert!(trace.contains("Final Facts"));
+    }
+
+    #[test]
+    fn marks_do_not_change_the_analysis() {
+        let unmarked = Minefield::new_active_game("m1\ne1\nee");
+        let expected = analyze(&unmarked);
+        for mark in [(0_usize, 0_usize), (0, 2)] {
+            let mut mf = Minefield::new_active_game("m1\ne1\nee");
+            mf.execute(PendingCommand::new(Location::from(mark), Action::Mark));
+            let analysis = analyze(&mf);
+            assert_eq!(
+                (analysis.safe, analysis.mines),
+                (expected.safe.clone(), expected.mines.clone()),
+                "mark at {:?}",
+                mark
+            );
+        }
+    }
+
+    #[test]
+    fn remaining_fact_is_only_seeded_for_few_locations() {
+        let mf = Minefield::new_active_game("m1\ne1\nee");
+        let remaining = Solver::new(&mf).remaining_fact().unwrap();
+        assert_eq!((remaining.count, remaining.cardinality()), (1, 4));
+
+        // 11 hidden cells
+        let mf = Minefield::new_active_game("meeeee\n1eeeee");
+        assert!(Solver::new(&mf).remaining_fact().is_none());
+    }
+
+    #[test]
+    fn facts_are_exported_as_dot_graph() {
+        let mf = Minefield::new_active_game("m1\ne1\nee");
+        let mut solver = Solver::new(&mf);
+        solver.seed_rules();
+        solver.deduce();
+        let mut dot = Vec::new();
+        solver.write_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+
+        assert!(dot.starts_with("digraph facts {"));
+        assert!(dot.trim_end().ends_with('}'));
+        let nodes = dot.lines().filter(|l| l.contains("fillcolor")).count();
+        // one node per fact and one legend node per rule, seeder and mine count
+        assert_eq!(nodes, solver.facts.len() + 2 + solver.rules.len());
+        assert!(dot.contains("[label=\"Exact 1\\n{(0,0), (0,1)}\", fillcolor=lightgrey"));
+        assert!(dot.contains("label=\"MinWithinMaxCombinator\""));
+        #[cfg(feature = "derived_from")]
+        assert!(dot.contains(" -> "));
+    }
+
     #[test]
     fn two_fact_safe_deduction() {
         let grid = "m1
                          e1
                          ee";
-        let mf = Minefield::new_active_game(&grid);
+        let mf = Minefield::new_active_game(grid);
 
         let (safe, mine) = Solver::solve_dump(&mf, dump_facts_path().as_deref());
 
@@ -660,7 +1148,7 @@ mod tests {
     fn two_fact_mine_and_safe_deduction() {
         let grid = "mmeee
                          2211m";
-        let mf = Minefield::new_active_game(&grid);
+        let mf = Minefield::new_active_game(grid);
 
         let (safe, mine) = Solver::solve_dump(&mf, dump_facts_path().as_deref());
 
@@ -673,7 +1161,7 @@ mod tests {
         let grid = "eeeee
                          em1ee
                          e111m";
-        let mf = Minefield::new_active_game(&grid);
+        let mf = Minefield::new_active_game(grid);
 
         let (safe, mine) = Solver::solve_dump(&mf, dump_facts_path().as_deref());
 
@@ -686,7 +1174,7 @@ mod tests {
         let grid = "12m1
                          em32
                          ee2m";
-        let mf = Minefield::new_active_game(&grid);
+        let mf = Minefield::new_active_game(grid);
 
         let (safe, mine) = Solver::solve_dump(&mf, dump_facts_path().as_deref());
 
@@ -699,7 +1187,7 @@ mod tests {
         let grid = "12m1
                          me32
                          em2m";
-        let mf = Minefield::new_active_game(&grid);
+        let mf = Minefield::new_active_game(grid);
 
         let (safe, mine) = Solver::solve_dump(&mf, dump_facts_path().as_deref());
 
@@ -712,7 +1200,7 @@ mod tests {
         let grid = "12m1
                          me32
                          mm2m";
-        let mf = Minefield::new_active_game(&grid);
+        let mf = Minefield::new_active_game(grid);
 
         let (safe, mine) = Solver::solve_dump(&mf, dump_facts_path().as_deref());
 
@@ -732,7 +1220,7 @@ mod tests {
                          meeem1001110000002meeeee
                          eeme21000000001122eeeemm
                          eemm10000000001memeeeeem";
-        let mf = Minefield::new_active_game(&grid);
+        let mf = Minefield::new_active_game(grid);
 
         let (safe, mine) = Solver::solve_dump(&mf, dump_facts_path().as_deref());
 
@@ -774,8 +1262,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn facts_do_not_depend_on_thread_count() {
+        let grid = "meeeeeeeem100000000001em
+                         eeemeeeeee111101110113me
+                         emeemmemeeeem101m212meme
+                         emeee3212m11110113meeeem
+                         meeem1001110000002meeeee
+                         eeme21000000001122eeeemm
+                         eemm10000000001memeeeeem";
+        let facts_with_threads = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let mf = Minefield::new_active_game(grid);
+                let mut solver = Solver::new(&mf);
+                solver.seed_rules();
+                solver.deduce();
+                let facts: Vec<_> = solver
+                    .facts
+                    .iter()
+                    .map(|f| (f.iteration, f.clone()))
+                    .collect();
+                format!("{:?}", facts)
+            })
+        };
+
+        let serial = facts_with_threads(1);
+        assert_eq!(serial, facts_with_threads(2));
+        assert_eq!(serial, facts_with_threads(4));
+    }
+
+    #[test]
+    fn components_are_deduced_like_the_whole_board() {
+        let grid = "meeeeeeeem100000000001em
+                         eeemeeeeee111101110113me
+                         emeemmemeeeem101m212meme
+                         emeee3212m11110113meeeem
+                         meeem1001110000002meeeee
+                         eeme21000000001122eeeemm
+                         eemm10000000001memeeeeem";
+        let mf = Minefield::new_active_game(grid);
+        let mut seeded = Solver::new(&mf);
+        seeded.seed();
+        assert!(seeded.facts.into_components().len() > 1);
+
+        let deduce = |traced: bool| {
+            let mut trace = Vec::new();
+            let mut solver = Solver::new(&mf);
+            solver.seed_rules();
+            if traced {
+                solver.trace = Some(Box::new(&mut trace));
+            }
+            solver.deduce();
+            (
+                solver.analysis(),
+                solver.iter().cloned().collect::<Vec<_>>(),
+            )
+        };
+        let (split, split_facts) = deduce(false);
+        let (whole, whole_facts) = deduce(true);
+        assert_eq!(split, whole);
+        check_facts(whole_facts, split_facts);
+    }
+
     fn locations<const N: usize>(ls: [(usize, usize); N]) -> HashSet<Location> {
-        std::array::IntoIter::new(ls).map(Into::into).collect()
+        IntoIterator::into_iter(ls).map(Into::into).collect()
     }
 
     fn fact<const N: usize>(
@@ -783,9 +1337,7 @@ mod tests {
         mine_count: usize,
         proximity: [(usize, usize); N],
     ) -> Fact {
-        let proximity = std::array::IntoIter::new(proximity)
-            .map(Into::into)
-            .collect();
+        let proximity = IntoIterator::into_iter(proximity).map(Into::into).collect();
         Fact::new(
             Constraint::Exact,
             mine_count,
@@ -801,7 +1353,7 @@ mod tests {
         println!("Expected: {:?}", expected);
         println!("Actual: {:?}", actual);
         assert_eq!(expected.len(), actual.len(), "Different number of facts!");
-        for (e, a) in expected.into_iter().zip(actual.into_iter()) {
+        for (e, a) in expected.into_iter().zip(actual) {
             assert_eq!(e, a);
         }
     }
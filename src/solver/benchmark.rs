@@ -0,0 +1,94 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::core::{GameState, Parameters};
+
+use super::{random_positions, SolverBackend};
+
+/// How one backend fared in a [`benchmark`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BenchmarkResult {
+    pub backend: &'static str,
+    pub positions: usize,
+    /// Number of safe cells and mines the backend found in all positions.
+    pub forced: usize,
+    pub total_time: Duration,
+}
+
+impl BenchmarkResult {
+    pub fn forced_per_position(&self) -> f64 {
+        self.forced as f64 / self.positions.max(1) as f64
+    }
+
+    pub fn time_per_position(&self) -> Duration {
+        self.total_time / self.positions.max(1) as u32
+    }
+}
+
+impl fmt::Display for BenchmarkResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:<6} {} positions, {:.2} forced cells per position, {:.3} ms per position",
+            self.backend,
+            self.positions,
+            self.forced_per_position(),
+            self.time_per_position().as_secs_f64() * 1000.0
+        )
+    }
+}
+
+/// Lets every backend solve the same `positions` random positions and measures how many cells
+/// it decides and how long it takes.
+///
+/// The positions are those of [`differential_test`](super::differential_test), finished games
+/// are left out.
+pub fn benchmark(
+    params: Parameters,
+    positions: usize,
+    backends: &[&dyn SolverBackend],
+) -> Vec<BenchmarkResult> {
+    let games: Vec<_> = random_positions(params, positions)
+        .into_iter()
+        .filter(|game| matches!(game.state(), GameState::InProgress { .. }))
+        .collect();
+
+    backends
+        .iter()
+        .map(|backend| {
+            let start = Instant::now();
+            let forced = games
+                .iter()
+                .map(|game| {
+                    let (safe, mines) = backend.solve(game);
+                    safe.len() + mines.len()
+                })
+                .sum();
+            BenchmarkResult {
+                backend: backend.name(),
+                positions: games.len(),
+                forced,
+                total_time: start.elapsed(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{DpllBackend, GaussBackend, RuleBackend};
+
+    #[test]
+    fn complete_backend_finds_the_most_cells() {
+        let params = Parameters::new(8, 6, 9).with_seed(5);
+        let results = benchmark(params, 10, &[&RuleBackend, &GaussBackend, &DpllBackend]);
+        let names: Vec<_> = results.iter().map(|r| r.backend).collect();
+        assert_eq!(names, ["rules", "gauss", "dpll"]);
+        assert!(results.iter().all(|r| r.positions == results[0].positions));
+        assert!(results[0].positions > 0);
+        assert!(results[2].forced >= results[0].forced.max(results[1].forced));
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::HashSet;
+
+use crate::core::{Location, Minefield};
+
+use super::SolverBackend;
+
+/// Decides every hidden cell completely with a DPLL search over cardinality constraints.
+///
+/// Each revealed number says that exactly that many of its hidden neighbours are mines and the
+/// mine count says how many mines all hidden cells hold. A cell is forced if no assignment
+/// satisfying all constraints gives it the other value. Unlike the rules, this finds every
+/// forced cell, at the cost of a search that is exponential in the worst case.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DpllBackend;
+
+impl SolverBackend for DpllBackend {
+    fn name(&self) -> &'static str {
+        "dpll"
+    }
+
+    fn solve(&self, mf: &Minefield) -> (HashSet<Location>, HashSet<Location>) {
+        Problem::new(mf).forced()
+    }
+}
+
+/// Cardinality constraints over the hidden cells of a mine field.
+struct Problem {
+    cells: Vec<Location>,
+    /// The variables of each constraint and how many of them are mines.
+    constraints: Vec<(Vec<usize>, usize)>,
+    /// The constraints each variable appears in.
+    constraints_of: Vec<Vec<usize>>,
+}
+
+impl Problem {
+    fn new(mf: &Minefield) -> Self {
+        let fog = mf.fog();
+        let mut cells: Vec<_> = fog
+            .loc_iter()
+            .filter(|(_, s)| s.is_hidden() || s.is_marked())
+            .map(|(l, _)| l)
+            .collect();
+        cells.sort_unstable();
+        let variable = |l: Location| cells.binary_search(&l).ok();
+
+        let numbers = fog.loc_iter().filter_map(|(l, s)| {
+            let neighbours: Vec<_> = l.neighbours().filter_map(variable).collect();
+            Some((neighbours, *s.as_revealed()?))
+        });
+        let exploded = fog.iter().filter(|s| s.is_exploded()).count();
+        let all_cells = (
+            (0..cells.len()).collect(),
+            mf.mine_count().saturating_sub(exploded),
+        );
+        let constraints: Vec<_> = numbers.chain(std::iter::once(all_cells)).collect();
+
+        let mut constraints_of = vec![Vec::new(); cells.len()];
+        for (c, (variables, _)) in constraints.iter().enumerate() {
+            for &v in variables {
+                constraints_of[v].push(c);
+            }
+        }
+        Self {
+            cells,
+            constraints,
+            constraints_of,
+        }
+    }
+
+    /// Returns an assignment that satisfies all constraints and the given assumptions.
+    fn model(&self, assumptions: &[(usize, bool)]) -> Option<Vec<bool>> {
+        let mut search = Search::new(self);
+        let consistent = search.check_all()
+            && assumptions
+                .iter()
+                .all(|&(variable, value)| search.propagate(variable, value));
+        match consistent && search.search() {
+            true => Some(search.values.iter().map(|v| v.unwrap_or(false)).collect()),
+            false => None,
+        }
+    }
+
+    /// Returns the safe cells and the mines that have the same value in all models.
+    /// An inconsistent problem has no forced cells.
+    fn forced(&self) -> (HashSet<Location>, HashSet<Location>) {
+        let mut safe = HashSet::new();
+        let mut mines = HashSet::new();
+        let model = match self.model(&[]) {
+            Some(model) => model,
+            None => return (safe, mines),
+        };
+        // Which values have been seen in some model, so most cells need no extra search.
+        let mut seen = vec![[false; 2]; self.cells.len()];
+        let record = |model: &[bool], seen: &mut Vec<[bool; 2]>| {
+            for (seen, &value) in seen.iter_mut().zip(model) {
+                seen[value as usize] = true;
+            }
+        };
+        record(&model, &mut seen);
+
+        for (variable, &cell) in self.cells.iter().enumerate() {
+            if seen[variable] == [true, true] {
+                continue;
+            }
+            let value = model[variable];
+            match self.model(&[(variable, !value)]) {
+                Some(other) => record(&other, &mut seen),
+                None if value => {
+                    mines.insert(cell);
+                }
+                None => {
+                    safe.insert(cell);
+                }
+            }
+        }
+        (safe, mines)
+    }
+}
+
+/// The state of a DPLL search with unit propagation for cardinality constraints.
+struct Search<'p> {
+    problem: &'p Problem,
+    values: Vec<Option<bool>>,
+    /// Number of variables assigned to be a mine per constraint.
+    mines: Vec<usize>,
+    /// Number of unassigned variables per constraint.
+    unassigned: Vec<usize>,
+    /// The assigned variables in order, to undo assignments when backtracking.
+    trail: Vec<usize>,
+}
+
+impl<'p> Search<'p> {
+    fn new(problem: &'p Problem) -> Self {
+        Self {
+            problem,
+            values: vec![None; problem.cells.len()],
+            mines: vec![0; problem.constraints.len()],
+            unassigned: problem.constraints.iter().map(|(v, _)| v.len()).collect(),
+            trail: Vec::new(),
+        }
+    }
+
+    /// Propagates the constraints that are decided before anything is assigned.
+    fn check_all(&mut self) -> bool {
+        (0..self.problem.constraints.len()).all(|c| {
+            let mut forced = Vec::new();
+            self.check(c, &mut forced)
+                && forced
+                    .into_iter()
+                    .all(|(v, value)| self.propagate(v, value))
+        })
+    }
+
+    /// Returns `false` if constraint `c` is violated and collects the values it forces.
+    fn check(&self, c: usize, forced: &mut Vec<(usize, bool)>) -> bool {
+        let (variables, count) = &self.problem.constraints[c];
+        let (mines, unassigned) = (self.mines[c], self.unassigned[c]);
+        if mines > *count || mines + unassigned < *count {
+            return false;
+        }
+        if unassigned > 0 && (mines == *count || mines + unassigned == *count) {
+            let value = mines < *count;
+            let unassigned_variables = variables.iter().filter(|&&v| self.values[v].is_none());
+            forced.extend(unassigned_variables.map(|&v| (v, value)));
+        }
+        true
+    }
+
+    /// Assigns `value` to `variable` and everything that follows from it.
+    /// Returns `false` on a conflict, the assignments stay on the trail until they are undone.
+    fn propagate(&mut self, variable: usize, value: bool) -> bool {
+        let mut queue = vec![(variable, value)];
+        while let Some((variable, value)) = queue.pop() {
+            match self.values[variable] {
+                Some(assigned) if assigned == value => continue,
+                Some(_) => return false,
+                None => {}
+            }
+            self.values[variable] = Some(value);
+            self.trail.push(variable);
+            for &c in &self.problem.constraints_of[variable] {
+                self.unassigned[c] -= 1;
+                self.mines[c] += value as usize;
+            }
+            for &c in &self.problem.constraints_of[variable] {
+                if !self.check(c, &mut queue) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn undo(&mut self, trail_length: usize) {
+        while self.trail.len() > trail_length {
+            let variable = self.trail.pop().expect("Trail is longer than its length.");
+            let value = self.values[variable]
+                .take()
+                .expect("Trail holds assigned variables.");
+            for &c in &self.problem.constraints_of[variable] {
+                self.unassigned[c] += 1;
+                self.mines[c] -= value as usize;
+            }
+        }
+    }
+
+    /// Picks an unassigned variable of the constraint with the fewest unassigned variables,
+    /// which is the most likely to fail early.
+    fn branch_variable(&self) -> Option<usize> {
+        let (c, _) = (0..self.problem.constraints.len())
+            .map(|c| (c, self.unassigned[c]))
+            .filter(|&(_, unassigned)| unassigned > 0)
+            .min_by_key(|&(_, unassigned)| unassigned)?;
+        self.problem.constraints[c]
+            .0
+            .iter()
+            .copied()
+            .find(|&v| self.values[v].is_none())
+    }
+
+    /// Completes the assignment, returns `false` if that is impossible.
+    fn search(&mut self) -> bool {
+        let variable = match self.branch_variable() {
+            Some(variable) => variable,
+            None => return true,
+        };
+        for &value in &[false, true] {
+            let trail_length = self.trail.len();
+            if self.propagate(variable, value) && self.search() {
+                return true;
+            }
+            self.undo(trail_length);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::Parameters,
+        solver::{differential_test, RuleBackend},
+    };
+
+    fn locations<const N: usize>(ls: [(usize, usize); N]) -> HashSet<Location> {
+        IntoIterator::into_iter(ls).map(Into::into).collect()
+    }
+
+    #[test]
+    fn dpll_uses_total_mine_count() {
+        let mf = Minefield::new_active_game(
+            "meee
+             11em
+             0011",
+        );
+        let (safe, mines) = DpllBackend.solve(&mf);
+        assert_eq!(safe, locations([(2, 0), (2, 1), (3, 0)]));
+        assert_eq!(mines, locations([(3, 1)]));
+
+        let mf = Minefield::new_active_game("me\n11");
+        assert_eq!(DpllBackend.solve(&mf), (locations([]), locations([])));
+    }
+
+    #[test]
+    fn dpll_agrees_with_rules_where_rules_decide() {
+        let mf = Minefield::new_active_game(
+            "12m1
+             em32
+             ee2m",
+        );
+        let (rule_safe, rule_mines) = RuleBackend.solve(&mf);
+        let (safe, mines) = DpllBackend.solve(&mf);
+        assert!(rule_safe.is_subset(&safe));
+        assert!(rule_mines.is_subset(&mines));
+    }
+
+    #[test]
+    fn dpll_is_sound_and_complete_on_random_positions() {
+        let params = Parameters::new(6, 6, 7).with_seed(7);
+        let report = differential_test(params, 40, &DpllBackend);
+        assert!(report.positions > 20, "{}", report);
+        assert_eq!(report.unsound() + report.incomplete(), 0, "{}", report);
+    }
+}
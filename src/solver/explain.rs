@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use crate::core::{Location, Minefield};
+
+use super::{Constraint, Fact, Solver};
+
+/// Explains why the solver knows that `location` is safe or a mine, one readable step per line.
+///
+/// The steps follow the `derived_from` chain of the fact that proves the location, starting with
+/// the numbers on the board. The solver is run once more for this with derivations tracked.
+/// Returns `None` if the location cannot be deduced.
+pub fn explain(mf: &Minefield, location: Location) -> Option<Vec<String>> {
+    let mut solver = Solver::new(mf);
+    solver.track_derivations = true;
+    solver.seed_rules();
+    solver.deduce();
+
+    // The earliest fact has the shortest chain.
+    let proof = solver
+        .iter()
+        .filter(|f| f.is_exact() && f.proximity.contains(&location))
+        .filter(|f| f.count == 0 || f.count == f.cardinality())
+        .min_by_key(|f| (f.iteration, f.cardinality()))?;
+
+    let mut explanation = Explanation::default();
+    explanation.add(proof);
+    let conclusion = match proof.count {
+        0 => format!("Therefore {} is safe.", location),
+        _ => format!("Therefore {} is a mine.", location),
+    };
+    explanation.lines.push(conclusion);
+    Some(explanation.lines)
+}
+
+#[derive(Default)]
+struct Explanation {
+    lines: Vec<String>,
+    /// The number of the step that states each fact.
+    steps: HashMap<Fact, usize>,
+}
+
+impl Explanation {
+    /// Adds the steps leading to `fact` and returns the number of the step stating it.
+    fn add(&mut self, fact: &Fact) -> usize {
+        if let Some(&step) = self.steps.get(fact) {
+            return step;
+        }
+
+        let parents = &fact.debug.derived_from;
+        let step = match (fact.debug.base_location, parents.as_slice()) {
+            (Some(l), _) => self.push(format!(
+                "{} shows {} and its hidden neighbours are {}.",
+                l,
+                fact.count,
+                cells(fact)
+            )),
+            (None, []) => self.push(format!(
+                "The remaining {} are somewhere in {}.",
+                mines(fact.count),
+                cells(fact)
+            )),
+            // Converting between exact, minimum and maximum facts about the same cells
+            // only restates the parent.
+            (None, [parent])
+                if parent.count == fact.count && parent.proximity == fact.proximity =>
+            {
+                self.add(parent)
+            }
+            (None, parents) => {
+                let premises: Vec<_> = parents.iter().map(|p| self.add(p).to_string()).collect();
+                self.push(format!(
+                    "From {}: {}.",
+                    premises.join(" and "),
+                    statement(fact)
+                ))
+            }
+        };
+        self.steps.insert(fact.clone(), step);
+        step
+    }
+
+    fn push(&mut self, line: String) -> usize {
+        let step = self.lines.len() + 1;
+        self.lines.push(format!("{}. {}", step, line));
+        step
+    }
+}
+
+fn cells(fact: &Fact) -> String {
+    let cells: Vec<_> = fact.proximity.iter().map(|l| l.to_string()).collect();
+    format!("{{{}}}", cells.join(", "))
+}
+
+fn mines(count: usize) -> String {
+    match count {
+        1 => "1 mine".into(),
+        n => format!("{} mines", n),
+    }
+}
+
+/// Describes what a fact says about its cells.
+fn statement(fact: &Fact) -> String {
+    let single = fact.cardinality() == 1;
+    let subject = match fact.proximity.iter().next() {
+        Some(l) if single => l.to_string(),
+        _ => cells(fact),
+    };
+    let all = fact.cardinality();
+    match (fact.kind, fact.count) {
+        (Constraint::Exact | Constraint::Max, 0) if single => format!("{} is safe", subject),
+        (Constraint::Exact | Constraint::Max, 0) => format!("{} are safe", subject),
+        (Constraint::Exact | Constraint::Min, n) if n == all && single => {
+            format!("{} is a mine", subject)
+        }
+        (Constraint::Exact | Constraint::Min, n) if n == all => {
+            format!("{} are mines", subject)
+        }
+        (Constraint::Exact, n) => format!("{} hold exactly {}", subject, mines(n)),
+        (Constraint::Min, n) => format!("{} hold at least {}", subject, mines(n)),
+        (Constraint::Max, n) => format!("{} hold at most {}", subject, mines(n)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explains_deduction_from_two_numbers() {
+        let mf = Minefield::new_active_game(
+            "mee
+             11e",
+        );
+        let explanation = explain(&mf, Location::new(2_usize, 0_usize)).unwrap();
+        assert_eq!(
+            explanation,
+            vec![
+                "1. (0,1) shows 1 and its hidden neighbours are {(0,0), (1,0)}.",
+                "2. (1,1) shows 1 and its hidden neighbours are {(0,0), (1,0), (2,0), (2,1)}.",
+                "3. From 1 and 2: {(2,0), (2,1)} are safe.",
+                "Therefore (2,0) is safe.",
+            ]
+        );
+    }
+
+    #[test]
+    fn explains_mine() {
+        let mf = Minefield::new_active_game("m1");
+        let explanation = explain(&mf, Location::new(0_usize, 0_usize)).unwrap();
+        assert_eq!(
+            explanation,
+            vec![
+                "1. (1,0) shows 1 and its hidden neighbours are {(0,0)}.",
+                "Therefore (0,0) is a mine.",
+            ]
+        );
+    }
+
+    #[test]
+    fn guesses_cannot_be_explained() {
+        let mf = Minefield::new_active_game(
+            "me
+             11",
+        );
+        assert_eq!(explain(&mf, Location::new(0_usize, 0_usize)), None);
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::core::Location;
+
+use super::{Fact, Proximity};
+
+/// The facts of the [`Solver`](super::Solver), indexed by the iteration that produced them and
+/// by the locations they are about.
+///
+/// A fact that is subsumed by a stored fact about the same locations is not stored, and stored
+/// facts that a new fact subsumes are removed, see [`Fact::subsumes`].
+#[derive(Clone, Default)]
+pub struct FactStore {
+    /// Removed facts leave an empty slot, so the ids in the indices stay valid.
+    slots: Vec<Option<Fact>>,
+    len: usize,
+    by_proximity: HashMap<Proximity, Vec<usize>>,
+    by_iteration: Vec<Vec<usize>>,
+    by_location: HashMap<Location, Vec<usize>>,
+}
+
+impl FactStore {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Fact> {
+        self.slots.iter().flatten()
+    }
+
+    /// Returns the facts produced in `iteration`.
+    pub fn iteration(&self, iteration: usize) -> impl Iterator<Item = &Fact> {
+        let ids = self.by_iteration.get(iteration).into_iter().flatten();
+        ids.filter_map(move |&id| self.slots[id].as_ref())
+    }
+
+    /// Returns the facts that share at least one location with `fact`, each of them once.
+    pub fn overlapping<'a>(&'a self, fact: &'a Fact) -> impl Iterator<Item = &'a Fact> {
+        fact.proximity.iter().flat_map(move |l| {
+            let ids = self.by_location.get(&l).into_iter().flatten();
+            ids.filter_map(move |&id| self.slots[id].as_ref())
+                // A fact that shares several locations is only returned for the first of them.
+                .filter(move |other| fact.proximity.first_common(&other.proximity) == Some(l))
+        })
+    }
+
+    /// Stores the fact unless a stored fact subsumes it and removes the stored facts it
+    /// subsumes. Returns whether the fact was stored.
+    pub fn insert(&mut self, fact: Fact) -> bool {
+        let same_proximity = self
+            .by_proximity
+            .get(&fact.proximity)
+            .map_or(&[][..], Vec::as_slice);
+        let stored = |id: &usize| self.slots[*id].as_ref();
+        if same_proximity
+            .iter()
+            .filter_map(stored)
+            .any(|f| f.subsumes(&fact))
+        {
+            return false;
+        }
+        let subsumed: Vec<_> = same_proximity
+            .iter()
+            .copied()
+            .filter(|id| stored(id).is_some_and(|f| fact.subsumes(f)))
+            .collect();
+        for id in subsumed {
+            self.slots[id] = None;
+            self.len -= 1;
+        }
+        self.push(fact);
+        true
+    }
+
+    /// Stores the fact without looking for facts that subsume it or that it subsumes.
+    fn push(&mut self, fact: Fact) {
+        let id = self.slots.len();
+        if self.by_iteration.len() <= fact.iteration {
+            self.by_iteration.resize_with(fact.iteration + 1, Vec::new);
+        }
+        self.by_iteration[fact.iteration].push(id);
+        for l in fact.proximity.iter() {
+            self.by_location.entry(l).or_default().push(id);
+        }
+        let slots = &self.slots;
+        let same_proximity = self.by_proximity.entry(fact.proximity.clone()).or_default();
+        same_proximity.retain(|&id| slots[id].is_some());
+        same_proximity.push(id);
+        self.slots.push(Some(fact));
+        self.len += 1;
+    }
+
+    /// Moves the facts of `other` into this store. The stores must not have facts about the
+    /// same locations, like two components of [`FactStore::into_components`].
+    pub fn append(&mut self, other: FactStore) {
+        for fact in other.slots.into_iter().flatten() {
+            self.push(fact);
+        }
+    }
+
+    /// Splits the store into the groups of facts that are connected by shared locations.
+    ///
+    /// Rules only combine facts about common locations, so facts in different components never
+    /// meet and each component can be deduced on its own. The components are ordered by their
+    /// oldest fact and keep the order of their facts.
+    pub fn into_components(self) -> Vec<FactStore> {
+        let mut component_of = vec![None; self.slots.len()];
+        let mut visited_locations = HashSet::new();
+        let mut components = Vec::new();
+        for start in 0..self.slots.len() {
+            if self.slots[start].is_none() || component_of[start].is_some() {
+                continue;
+            }
+            let component = components.len();
+            component_of[start] = Some(component);
+            let mut ids = Vec::new();
+            let mut stack = vec![start];
+            while let Some(id) = stack.pop() {
+                ids.push(id);
+                let fact = self.slots[id]
+                    .as_ref()
+                    .expect("Only stored facts are visited.");
+                for l in fact.proximity.iter() {
+                    if !visited_locations.insert(l) {
+                        continue;
+                    }
+                    for &other in &self.by_location[&l] {
+                        if self.slots[other].is_some() && component_of[other].is_none() {
+                            component_of[other] = Some(component);
+                            stack.push(other);
+                        }
+                    }
+                }
+            }
+            ids.sort_unstable();
+            components.push(ids);
+        }
+
+        let mut slots = self.slots;
+        components
+            .into_iter()
+            .map(|ids| {
+                let mut store = Self::default();
+                for id in ids {
+                    store.push(slots[id].take().expect("Facts are in one component."));
+                }
+                store
+            })
+            .collect()
+    }
+
+    /// Keeps only the facts for which `keep` returns `true` and rebuilds the indices.
+    pub fn retain<F: FnMut(&Fact) -> bool>(&mut self, mut keep: F) {
+        let facts = std::mem::take(&mut self.slots);
+        *self = Self::default();
+        for fact in facts.into_iter().flatten().filter(|f| keep(f)) {
+            self.insert(fact);
+        }
+    }
+}
+
+impl fmt::Debug for FactStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{Constraint, FactDebug, Seeder};
+
+    fn fact(kind: Constraint, count: usize, proximity: &[(usize, usize)]) -> Fact {
+        let proximity = proximity.iter().map(|&l| l.into()).collect();
+        Fact::new(kind, count, proximity, 0, FactDebug::base(None, &Seeder))
+    }
+
+    #[test]
+    fn weaker_facts_are_pruned() {
+        let mut store = FactStore::default();
+        let cells = [(0, 0), (1, 0), (2, 0)];
+        assert!(store.insert(fact(Constraint::Max, 2, &cells)));
+        assert!(!store.insert(fact(Constraint::Max, 3, &cells)));
+        assert!(store.insert(fact(Constraint::Max, 1, &cells)));
+        assert_eq!(store.len(), 1);
+
+        // An exact fact replaces the max fact with the same count, the combinators use it as both.
+        assert!(store.insert(fact(Constraint::Exact, 1, &cells)));
+        assert_eq!(store.len(), 1);
+        assert!(!store.insert(fact(Constraint::Max, 1, &cells)));
+        assert!(!store.insert(fact(Constraint::Min, 1, &cells)));
+        assert!(!store.insert(fact(Constraint::Min, 0, &cells)));
+        assert!(!store.insert(fact(Constraint::Exact, 1, &cells)));
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn overlapping_facts_are_found_once_and_form_components() {
+        let mut store = FactStore::default();
+        store.insert(fact(Constraint::Exact, 1, &[(0, 0), (1, 0)]));
+        store.insert(fact(Constraint::Exact, 1, &[(1, 0), (2, 0)]));
+        store.insert(fact(Constraint::Exact, 1, &[(5, 5)]));
+
+        let probe = fact(Constraint::Exact, 2, &[(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(store.overlapping(&probe).count(), 2);
+        assert_eq!(store.iteration(0).count(), 3);
+        assert_eq!(store.iteration(1).count(), 0);
+
+        let components = store.clone().into_components();
+        let sizes: Vec<_> = components.iter().map(FactStore::len).collect();
+        assert_eq!(sizes, [2, 1]);
+        let mut merged = FactStore::default();
+        components.into_iter().for_each(|c| merged.append(c));
+        assert_eq!(merged.len(), 3);
+
+        store.retain(|f| f.cardinality() == 2);
+        assert_eq!(store.overlapping(&probe).count(), 2);
+        assert_eq!(store.len(), 2);
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashSet;
+
+use crate::core::{Location, Minefield};
+
+use super::SolverBackend;
+
+/// Decides hidden cells by row-reducing the linear equations of the revealed numbers.
+///
+/// Each revealed number and the mine count is an equation over the hidden cells. Row reduction
+/// combines them into equations the pairwise rules never look at, and each reduced equation is
+/// checked for cells whose value is forced because every cell is either 0 or 1. Decided cells
+/// are substituted and the equations reduced again until nothing new is found. This is sound and
+/// polynomial, but unlike [`DpllBackend`](super::DpllBackend) it misses cells that only follow
+/// from case distinctions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GaussBackend;
+
+impl SolverBackend for GaussBackend {
+    fn name(&self) -> &'static str {
+        "gauss"
+    }
+
+    fn solve(&self, mf: &Minefield) -> (HashSet<Location>, HashSet<Location>) {
+        let equations = Equations::new(mf);
+        let mut safe = HashSet::new();
+        let mut mines = HashSet::new();
+        if let Some(values) = equations.forced() {
+            for (&cell, value) in equations.cells.iter().zip(values) {
+                match value {
+                    Some(true) => mines.insert(cell),
+                    Some(false) => safe.insert(cell),
+                    None => continue,
+                };
+            }
+        }
+        (safe, mines)
+    }
+}
+
+/// Linear equations over the hidden cells of a mine field.
+struct Equations {
+    cells: Vec<Location>,
+    /// The variables of each equation and how many of them are mines.
+    equations: Vec<(Vec<usize>, usize)>,
+}
+
+impl Equations {
+    fn new(mf: &Minefield) -> Self {
+        let fog = mf.fog();
+        let mut cells: Vec<_> = fog
+            .loc_iter()
+            .filter(|(_, s)| s.is_hidden() || s.is_marked())
+            .map(|(l, _)| l)
+            .collect();
+        cells.sort_unstable();
+        let variable = |l: Location| cells.binary_search(&l).ok();
+
+        let numbers = fog.loc_iter().filter_map(|(l, s)| {
+            let neighbours: Vec<_> = l.neighbours().filter_map(variable).collect();
+            Some((neighbours, *s.as_revealed()?))
+        });
+        let exploded = fog.iter().filter(|s| s.is_exploded()).count();
+        let all_cells = (
+            (0..cells.len()).collect(),
+            mf.mine_count().saturating_sub(exploded),
+        );
+        let equations = numbers.chain(std::iter::once(all_cells)).collect();
+        Self { cells, equations }
+    }
+
+    /// Returns the value of every variable that is forced, or `None` if the equations have no
+    /// solution.
+    fn forced(&self) -> Option<Vec<Option<bool>>> {
+        let mut values = vec![None; self.cells.len()];
+        loop {
+            let mut rows = self.substituted(&values)?;
+            reduce(&mut rows);
+            let mut progress = false;
+            for row in &rows {
+                for (variable, value) in row.forced()? {
+                    progress |= values[variable].is_none();
+                    values[variable] = Some(value);
+                }
+            }
+            if !progress {
+                return Some(values);
+            }
+        }
+    }
+
+    /// Builds the rows of the equations with the known values substituted.
+    /// Returns `None` if an equation needs more mines than its cells can hold or fewer than zero.
+    fn substituted(&self, values: &[Option<bool>]) -> Option<Vec<Row>> {
+        self.equations
+            .iter()
+            .map(|(variables, count)| {
+                let mut row = Row {
+                    coefficients: vec![0; values.len()],
+                    value: *count as i64,
+                };
+                for &v in variables {
+                    match values[v] {
+                        Some(mine) => row.value -= mine as i64,
+                        None => row.coefficients[v] = 1,
+                    }
+                }
+                Some(row).filter(|row| row.value >= 0)
+            })
+            .collect()
+    }
+}
+
+/// The equation `coefficients · x = value` with integer coefficients.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Row {
+    coefficients: Vec<i64>,
+    value: i64,
+}
+
+impl Row {
+    /// Returns the variables that have the same value in all 0/1 solutions of the row, or `None`
+    /// if there is no such solution.
+    ///
+    /// The other variables add up to somewhere between the sum of the negative and the sum of the
+    /// positive coefficients. A value of a variable is impossible if the rest of the row cannot
+    /// make up the difference to `value`.
+    fn forced(&self) -> Option<Vec<(usize, bool)>> {
+        let min: i64 = self.coefficients.iter().filter(|&&c| c < 0).sum();
+        let max: i64 = self.coefficients.iter().filter(|&&c| c > 0).sum();
+        if self.value < min || self.value > max {
+            return None;
+        }
+        let mut forced = Vec::new();
+        for (variable, &c) in self.coefficients.iter().enumerate() {
+            if c == 0 {
+                continue;
+            }
+            let (rest_min, rest_max) = (min - c.min(0), max - c.max(0));
+            let possible = |sum: i64| rest_min <= sum && sum <= rest_max;
+            match (possible(self.value), possible(self.value - c)) {
+                (true, true) => {}
+                (true, false) => forced.push((variable, false)),
+                (false, true) => forced.push((variable, true)),
+                (false, false) => return None,
+            }
+        }
+        Some(forced)
+    }
+
+    /// Subtracts a multiple of `pivot` so that the coefficient of `column` becomes zero.
+    fn eliminate(&mut self, pivot: &Row, column: usize) {
+        let (a, p) = (self.coefficients[column], pivot.coefficients[column]);
+        for (c, &q) in self.coefficients.iter_mut().zip(&pivot.coefficients) {
+            *c = *c * p - q * a;
+        }
+        self.value = self.value * p - pivot.value * a;
+        self.normalize();
+    }
+
+    /// Divides the row by the greatest common divisor of its entries to keep them small.
+    fn normalize(&mut self) {
+        let divisor = self
+            .coefficients
+            .iter()
+            .fold(self.value, |divisor, &c| gcd(divisor, c));
+        if divisor > 1 {
+            self.coefficients.iter_mut().for_each(|c| *c /= divisor);
+            self.value /= divisor;
+        }
+    }
+}
+
+/// Brings the rows into reduced row echelon form without fractions.
+fn reduce(rows: &mut [Row]) {
+    let columns = rows.first().map_or(0, |row| row.coefficients.len());
+    let mut pivot_row = 0;
+    for column in 0..columns {
+        let pivot = match (pivot_row..rows.len()).find(|&r| rows[r].coefficients[column] != 0) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(pivot_row, pivot);
+        let (before, rest) = rows.split_at_mut(pivot_row);
+        let (pivot, after) = rest.split_first_mut().expect("The pivot row exists.");
+        for row in before.iter_mut().chain(after) {
+            if row.coefficients[column] != 0 {
+                row.eliminate(pivot, column);
+            }
+        }
+        pivot_row += 1;
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    match b {
+        0 => a.abs(),
+        _ => gcd(b, a % b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::Parameters,
+        solver::{differential_test, RuleBackend},
+    };
+
+    fn locations<const N: usize>(ls: [(usize, usize); N]) -> HashSet<Location> {
+        IntoIterator::into_iter(ls).map(Into::into).collect()
+    }
+
+    #[test]
+    fn row_bounds_force_variables() {
+        // x0 + x1 - x2 = 2 needs both positive cells to be mines and the negative one safe.
+        let row = Row {
+            coefficients: vec![1, 1, -1, 0],
+            value: 2,
+        };
+        assert_eq!(row.forced(), Some(vec![(0, true), (1, true), (2, false)]));
+
+        // 2 x0 + x1 + x2 = 2 forces nothing, but 2 x0 + x1 = 1 forces x0 to be safe. That x1 is
+        // a mine only follows once x0 is substituted.
+        let row = Row {
+            coefficients: vec![2, 1, 1],
+            value: 2,
+        };
+        assert_eq!(row.forced(), Some(vec![]));
+        let row = Row {
+            coefficients: vec![2, 1, 0],
+            value: 1,
+        };
+        assert_eq!(row.forced(), Some(vec![(0, false)]));
+
+        let row = Row {
+            coefficients: vec![1, 1],
+            value: 3,
+        };
+        assert_eq!(row.forced(), None);
+    }
+
+    #[test]
+    fn gauss_uses_total_mine_count() {
+        let mf = Minefield::new_active_game(
+            "meee
+             11em
+             0011",
+        );
+        let (safe, mines) = GaussBackend.solve(&mf);
+        assert_eq!(safe, locations([(2, 0), (2, 1), (3, 0)]));
+        assert_eq!(mines, locations([(3, 1)]));
+    }
+
+    #[test]
+    fn gauss_agrees_with_rules_where_rules_decide() {
+        let mf = Minefield::new_active_game(
+            "12m1
+             em32
+             ee2m",
+        );
+        let (rule_safe, rule_mines) = RuleBackend.solve(&mf);
+        let (safe, mines) = GaussBackend.solve(&mf);
+        assert!(rule_safe.is_subset(&safe));
+        assert!(rule_mines.is_subset(&mines));
+    }
+
+    #[test]
+    fn gauss_is_sound_on_random_positions() {
+        let params = Parameters::new(6, 6, 7).with_seed(7);
+        let report = differential_test(params, 40, &GaussBackend);
+        assert!(report.positions > 20, "{}", report);
+        assert_eq!(report.unsound(), 0, "{}", report);
+    }
+}
@@ -0,0 +1,262 @@
+use super::{
+    Constraint, Fact, FactDebug, Proximity, Rule, RuleInput, Solver, MAX_REMAINING_FACT_CARDINALITY,
+};
+
+/// Produces the facts that follow from the total number of mines, see
+/// [`Solver::seed_mine_count_facts`]. Like [`Seeder`](super::Seeder) it is not applied as a
+/// rule, it only marks the facts it produced.
+#[derive(Debug)]
+pub struct MineCount;
+
+impl Rule for MineCount {
+    fn derive(&self, _: &RuleInput) -> Vec<Fact> {
+        vec![]
+    }
+}
+
+/// A stored fact reduced to the locations that are not known yet, with bounds on its mines.
+struct Bounds<'f> {
+    proximity: Proximity,
+    min: usize,
+    max: usize,
+    fact: &'f Fact,
+}
+
+impl<'mf> Solver<'mf> {
+    /// Seeds facts that follow from the total number of mines in the current iteration and
+    /// returns whether any of them was new.
+    ///
+    /// Stored facts about disjoint locations hold at least the sum of their minimums and at most
+    /// the sum of their maximums, so the remaining mines bound the mines on all other locations,
+    /// and the other facts and locations bound the mines of each of them. If the facts need all
+    /// remaining mines, the locations next to no number are safe; if those locations have to
+    /// hold all mines the facts leave over, they are mines.
+    ///
+    /// The fact about all remaining locations is also seeded if it is small enough, see
+    /// [`MAX_REMAINING_FACT_CARDINALITY`].
+    pub(super) fn seed_mine_count_facts(&mut self) -> bool {
+        let known_mines = self.guaranteed_mines();
+        let known_safe = self.guaranteed_safe_locations();
+        let fog = self.mine_field.fog();
+        let exploded = fog.iter().filter(|s| s.is_exploded()).count();
+        let remaining_mines = match self
+            .mine_field
+            .mine_count()
+            .checked_sub(exploded + known_mines.len())
+        {
+            Some(remaining_mines) => remaining_mines,
+            // more mines than the mine field has, nothing can be derived
+            None => return false,
+        };
+        let unknown: Proximity = fog
+            .loc_iter()
+            .filter(|(_, s)| s.is_hidden() || s.is_marked())
+            .map(|(l, _)| l)
+            .filter(|l| !known_mines.contains(l) && !known_safe.contains(l))
+            .collect();
+
+        let bounds: Vec<_> = self
+            .facts
+            .iter()
+            .filter_map(|fact| {
+                let proximity = &fact.proximity & &unknown;
+                if proximity.is_empty() {
+                    return None;
+                }
+                let mines = fact.proximity.iter().filter(|l| known_mines.contains(l));
+                let mines = mines.count();
+                let (min, max) = match fact.kind {
+                    Constraint::Min => (fact.count, usize::MAX),
+                    Constraint::Exact => (fact.count, fact.count),
+                    Constraint::Max => (0, fact.count),
+                };
+                Some(Bounds {
+                    min: min.saturating_sub(mines),
+                    max: max.saturating_sub(mines).min(proximity.len()),
+                    proximity,
+                    fact,
+                })
+            })
+            .collect();
+
+        let mut new_facts = self.remaining_fact().into_iter().collect::<Vec<_>>();
+        new_facts.extend(self.lower_bound_facts(&bounds, &unknown, remaining_mines));
+        new_facts.extend(self.upper_bound_facts(&bounds, &unknown, remaining_mines));
+        self.add(new_facts)
+    }
+
+    /// Derives maximums from facts that need many mines.
+    fn lower_bound_facts(
+        &self,
+        bounds: &[Bounds],
+        unknown: &Proximity,
+        remaining_mines: usize,
+    ) -> Vec<Fact> {
+        let chosen = disjoint(bounds, |b| b.min);
+        let min: usize = chosen.iter().map(|b| b.min).sum();
+        let rest_max = match remaining_mines.checked_sub(min) {
+            Some(rest_max) => rest_max,
+            None => return vec![],
+        };
+
+        let rest = chosen
+            .iter()
+            .fold(unknown.clone(), |rest, b| &rest - &b.proximity);
+        let mut facts = self.rest_facts(Constraint::Max, rest_max, rest);
+        for b in chosen {
+            // the other chosen facts hold at least `min - b.min` of the remaining mines
+            let max = rest_max + b.min;
+            if max < b.max {
+                facts.push(self.mine_count_fact(Constraint::Max, max, b.proximity.clone(), b));
+            }
+        }
+        facts
+    }
+
+    /// Derives minimums from facts that leave room for few mines.
+    fn upper_bound_facts(
+        &self,
+        bounds: &[Bounds],
+        unknown: &Proximity,
+        remaining_mines: usize,
+    ) -> Vec<Fact> {
+        let chosen = disjoint(bounds, |b| b.proximity.len() - b.max);
+        let max: usize = chosen.iter().map(|b| b.max).sum();
+        let rest = chosen
+            .iter()
+            .fold(unknown.clone(), |rest, b| &rest - &b.proximity);
+        let rest_min = remaining_mines.saturating_sub(max);
+        if rest_min > rest.len() {
+            return vec![];
+        }
+
+        let rest_len = rest.len();
+        let mut facts = self.rest_facts(Constraint::Min, rest_min, rest);
+        for b in chosen {
+            // the other chosen facts and the rest hold at most this many of the remaining mines
+            let others = max - b.max + rest_len;
+            let min = remaining_mines.saturating_sub(others);
+            if min > b.min {
+                facts.push(self.mine_count_fact(Constraint::Min, min, b.proximity.clone(), b));
+            }
+        }
+        facts
+    }
+
+    /// Returns the facts about the locations that none of the chosen facts is about.
+    ///
+    /// A fact about many locations combines with most other facts, so only small ones are kept
+    /// as they are. Of larger ones, only decided locations are kept, one fact per location.
+    fn rest_facts(&self, kind: Constraint, count: usize, rest: Proximity) -> Vec<Fact> {
+        let (trivial, decided) = match kind {
+            Constraint::Max => (count >= rest.len(), count == 0),
+            _ => (count == 0, count == rest.len()),
+        };
+        if rest.is_empty() || trivial {
+            return vec![];
+        }
+        if rest.len() <= MAX_REMAINING_FACT_CARDINALITY {
+            return vec![self.rest_fact(kind, count, rest)];
+        }
+        if !decided {
+            return vec![];
+        }
+        let mines = (count > 0) as usize;
+        rest.iter()
+            .map(|l| self.rest_fact(Constraint::Exact, mines, std::iter::once(l).collect()))
+            .collect()
+    }
+
+    fn rest_fact(&self, kind: Constraint, count: usize, proximity: Proximity) -> Fact {
+        Fact::new(
+            kind,
+            count,
+            proximity,
+            self.iteration,
+            FactDebug::base(None, &MineCount),
+        )
+    }
+
+    fn mine_count_fact(
+        &self,
+        kind: Constraint,
+        count: usize,
+        proximity: Proximity,
+        bounds: &Bounds,
+    ) -> Fact {
+        Fact::new(
+            kind,
+            count,
+            proximity,
+            self.iteration,
+            FactDebug::derived_one(&MineCount, bounds.fact, self.track_derivations),
+        )
+    }
+}
+
+/// Picks facts about disjoint locations, preferring those with the largest `weight`.
+/// Facts with zero weight add nothing and are left out.
+fn disjoint<'b, 'f, W: Fn(&Bounds) -> usize>(
+    bounds: &'b [Bounds<'f>],
+    weight: W,
+) -> Vec<&'b Bounds<'f>> {
+    let mut candidates: Vec<_> = bounds.iter().filter(|b| weight(b) > 0).collect();
+    // smaller facts first among equal weights, they leave more room for others
+    candidates.sort_by(|l, r| {
+        weight(r)
+            .cmp(&weight(l))
+            .then_with(|| l.proximity.len().cmp(&r.proximity.len()))
+            .then_with(|| l.proximity.cmp(&r.proximity))
+    });
+
+    let mut covered = Proximity::new();
+    let mut chosen = Vec::new();
+    for b in candidates {
+        if covered.is_disjoint(&b.proximity) {
+            covered = &covered | &b.proximity;
+            chosen.push(b);
+        }
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        core::{Location, Minefield},
+        solver::analyze,
+    };
+
+    fn columns(from: usize, to: usize) -> HashSet<Location> {
+        (from..to)
+            .flat_map(|x| (0..2_usize).map(move |y| Location::new(x, y)))
+            .collect()
+    }
+
+    #[test]
+    fn interior_is_safe_if_the_frontier_needs_all_mines() {
+        // (0,0) and (1,0) hold the only mine, too many cells remain for the remaining fact.
+        let mf = Minefield::new_active_game(
+            "meeeeeeeeeee
+             11eeeeeeeeee",
+        );
+        let analysis = analyze(&mf);
+        assert_eq!(analysis.safe, columns(2, 12));
+        assert!(analysis.mines.is_empty());
+        assert!(analysis.facts_per_rule[MineCount.name()] > 0);
+    }
+
+    #[test]
+    fn interior_is_mined_if_the_frontier_has_room_for_few_mines() {
+        let mf = Minefield::new_active_game(
+            "meemmmmmmmmm
+             11emmmmmmmmm",
+        );
+        let analysis = analyze(&mf);
+        assert_eq!(analysis.safe, columns(2, 3));
+        assert_eq!(analysis.mines, columns(3, 12));
+    }
+}
@@ -0,0 +1,311 @@
+use std::{collections::HashSet, fmt};
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    core::{
+        Action, ExclusionZone, GameState, Location, Minefield, MinefieldGenerator, Parameters,
+        PendingCommand,
+    },
+    generator::SimpleGenerator,
+};
+
+use super::SolverBackend;
+
+/// The oracle refuses positions with more hidden cells next to numbers than this.
+pub const MAX_ORACLE_FRONTIER: usize = 20;
+
+/// Enumerates every mine assignment of the hidden cells that is consistent with the revealed
+/// numbers and the total mine count, and returns the cells that are safe in all of them and
+/// those that are mines in all of them.
+///
+/// The assignments of the frontier, i.e. the hidden cells next to numbers, are enumerated one
+/// by one. The other cells are interchangeable, so only the number of mines left for them
+/// matters. Marks are ignored.
+///
+/// Returns `None` if the frontier is larger than [`MAX_ORACLE_FRONTIER`] or no assignment is
+/// consistent.
+pub fn brute_force(mf: &Minefield) -> Option<(HashSet<Location>, HashSet<Location>)> {
+    let fog = mf.fog();
+    let is_unknown = |l: Location| fog.get(l).is_some_and(|s| s.is_hidden() || s.is_marked());
+    let numbers: Vec<_> = fog
+        .loc_iter()
+        .filter_map(|(l, s)| Some((l, *s.as_revealed()?)))
+        .filter(|(l, _)| l.neighbours().any(is_unknown))
+        .collect();
+    let mut frontier: Vec<_> = numbers
+        .iter()
+        .flat_map(|(l, _)| l.neighbours().filter(|&n| is_unknown(n)))
+        .collect();
+    frontier.sort_unstable();
+    frontier.dedup();
+    if frontier.len() > MAX_ORACLE_FRONTIER {
+        return None;
+    }
+    let others: Vec<_> = fog
+        .loc_iter()
+        .map(|(l, _)| l)
+        .filter(|&l| is_unknown(l) && frontier.binary_search(&l).is_err())
+        .collect();
+    let exploded = fog.iter().filter(|s| s.is_exploded()).count();
+    let remaining_mines = mf.mine_count().checked_sub(exploded)?;
+
+    // Bit i of `always` and `never` tracks whether frontier cell i was a mine in all or none
+    // of the consistent assignments so far.
+    let all_cells = (1u32 << frontier.len()).wrapping_sub(1);
+    let (mut always, mut never) = (all_cells, all_cells);
+    let (mut others_always, mut others_never) = (true, true);
+    let mut consistent = false;
+    for assignment in 0..=all_cells {
+        let mines = assignment.count_ones() as usize;
+        let other_mines = match remaining_mines.checked_sub(mines) {
+            Some(other_mines) if other_mines <= others.len() => other_mines,
+            _ => continue,
+        };
+        let is_mine = |l: Location| {
+            frontier
+                .binary_search(&l)
+                .is_ok_and(|i| assignment & (1 << i) != 0)
+        };
+        let matches_numbers = numbers
+            .iter()
+            .all(|&(l, count)| l.neighbours().filter(|&n| is_mine(n)).count() == count);
+        if !matches_numbers {
+            continue;
+        }
+
+        consistent = true;
+        always &= assignment;
+        never &= !assignment;
+        others_always &= other_mines == others.len();
+        others_never &= other_mines == 0;
+    }
+    if !consistent {
+        return None;
+    }
+
+    let frontier_where = |bits: u32| {
+        frontier
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| bits & (1 << i) != 0)
+            .map(|(_, &l)| l)
+    };
+    let others_if = |all: bool| others.iter().copied().filter(move |_| all);
+    let safe = frontier_where(never)
+        .chain(others_if(others_never))
+        .collect();
+    let mines = frontier_where(always)
+        .chain(others_if(others_always))
+        .collect();
+    Some((safe, mines))
+}
+
+/// A difference between a [`SolverBackend`] and the [`brute_force`] oracle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Finding {
+    /// The solver claims that the location is a mine or safe, but the oracle found an
+    /// assignment where it is not.
+    Unsound {
+        location: Location,
+        claimed_mine: bool,
+    },
+    /// The oracle proves that the location is a mine or safe, but the solver misses it.
+    Incomplete { location: Location, is_mine: bool },
+}
+
+impl Finding {
+    pub fn is_unsound(&self) -> bool {
+        matches!(self, Finding::Unsound { .. })
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = |mine| if mine { "a mine" } else { "safe" };
+        match *self {
+            Finding::Unsound {
+                location,
+                claimed_mine,
+            } => write!(
+                f,
+                "unsound: solver claims {} is {}",
+                location,
+                kind(claimed_mine)
+            ),
+            Finding::Incomplete { location, is_mine } => write!(
+                f,
+                "incomplete: solver misses that {} is {}",
+                location,
+                kind(is_mine)
+            ),
+        }
+    }
+}
+
+/// Compares the solver backend with the oracle on one position.
+/// Returns `None` if the oracle cannot decide the position.
+pub fn compare_with_oracle(mf: &Minefield, backend: &dyn SolverBackend) -> Option<Vec<Finding>> {
+    let (oracle_safe, oracle_mines) = brute_force(mf)?;
+    let (solver_safe, solver_mines) = backend.solve(mf);
+
+    let mut findings = Vec::new();
+    let mut differences = |claimed: &HashSet<Location>, proven: &HashSet<Location>, mine| {
+        let mut unsound: Vec<_> = claimed.difference(proven).copied().collect();
+        let mut incomplete: Vec<_> = proven.difference(claimed).copied().collect();
+        unsound.sort_unstable();
+        incomplete.sort_unstable();
+        findings.extend(unsound.into_iter().map(|location| Finding::Unsound {
+            location,
+            claimed_mine: mine,
+        }));
+        findings.extend(incomplete.into_iter().map(|location| Finding::Incomplete {
+            location,
+            is_mine: mine,
+        }));
+    };
+    differences(&solver_safe, &oracle_safe, false);
+    differences(&solver_mines, &oracle_mines, true);
+    Some(findings)
+}
+
+/// The findings of [`differential_test`].
+#[derive(Clone, Debug, Default)]
+pub struct DifferentialReport {
+    /// Number of positions the solver was compared on.
+    pub positions: usize,
+    /// Number of positions the oracle could not decide.
+    pub skipped: usize,
+    /// The positions with findings, in the format of [`Minefield`]'s `Display`.
+    pub findings: Vec<(String, Vec<Finding>)>,
+}
+
+impl DifferentialReport {
+    pub fn unsound(&self) -> usize {
+        self.count(Finding::is_unsound)
+    }
+
+    pub fn incomplete(&self) -> usize {
+        self.count(|f| !f.is_unsound())
+    }
+
+    fn count<F: Fn(&Finding) -> bool>(&self, filter: F) -> usize {
+        self.findings
+            .iter()
+            .flat_map(|(_, findings)| findings)
+            .filter(|f| filter(f))
+            .count()
+    }
+}
+
+impl fmt::Display for DifferentialReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (position, findings) in &self.findings {
+            write!(f, "{}", position)?;
+            for finding in findings {
+                writeln!(f, "  {}", finding)?;
+            }
+        }
+        writeln!(
+            f,
+            "{} positions, {} skipped, {} unsound and {} incomplete results",
+            self.positions,
+            self.skipped,
+            self.unsound(),
+            self.incomplete()
+        )
+    }
+}
+
+/// Compares the solver backend with the oracle on `positions` random positions, see
+/// [`random_positions`].
+pub fn differential_test(
+    params: Parameters,
+    positions: usize,
+    backend: &dyn SolverBackend,
+) -> DifferentialReport {
+    let mut report = DifferentialReport::default();
+    for game in random_positions(params, positions) {
+        if !matches!(game.state(), GameState::InProgress { .. }) {
+            report.skipped += 1;
+            continue;
+        }
+
+        match compare_with_oracle(&game, backend) {
+            Some(findings) => {
+                report.positions += 1;
+                if !findings.is_empty() {
+                    report.findings.push((game.to_string(), findings));
+                }
+            }
+            None => report.skipped += 1,
+        }
+    }
+    report
+}
+
+/// Generates `count` boards with the parameters and a seed derived from `params`, opened in the
+/// center and then at up to three random safe cells. The same parameters yield the same
+/// positions. Some of the games may already be won.
+pub(crate) fn random_positions(params: Parameters, count: usize) -> Vec<Minefield> {
+    let mut rng = params.rng();
+    let click = Location::new(params.width / 2, params.height / 2);
+    let zone = ExclusionZone::for_first_click(&params, click);
+
+    (0..count)
+        .map(|_| {
+            let ground = SimpleGenerator
+                .generate(params.with_seed(rng.gen()), &zone)
+                .expect("The parameters leave room for the first click.");
+            let mut safe_cells: Vec<_> = ground
+                .loc_iter()
+                .filter(|(_, g)| g.is_dirt())
+                .map(|(l, _)| l)
+                .collect();
+            safe_cells.shuffle(&mut rng);
+            let extra_clicks = rng.gen_range(0..=3);
+
+            let mut game = Minefield::with_ground(ground);
+            let clicks = std::iter::once(click).chain(safe_cells.into_iter().take(extra_clicks));
+            for l in clicks {
+                game.execute(PendingCommand::new(l, Action::Reveal));
+            }
+            game
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::RuleBackend;
+
+    fn locations<const N: usize>(ls: [(usize, usize); N]) -> HashSet<Location> {
+        IntoIterator::into_iter(ls).map(Into::into).collect()
+    }
+
+    #[test]
+    fn oracle_uses_total_mine_count() {
+        // One mine is in the coin flip on top and one on the right, none is left for (3,0).
+        let mf = Minefield::new_active_game(
+            "meee
+             11em
+             0011",
+        );
+        let (safe, mines) = brute_force(&mf).unwrap();
+        assert_eq!(safe, locations([(2, 0), (2, 1), (3, 0)]));
+        assert_eq!(mines, locations([(3, 1)]));
+
+        // A coin flip without other cells cannot be decided, not even with the mine count.
+        let mf = Minefield::new_active_game("me\n11");
+        assert_eq!(brute_force(&mf), Some((locations([]), locations([]))));
+    }
+
+    #[test]
+    fn solver_is_sound_on_random_positions() {
+        let params = Parameters::new(5, 5, 5).with_seed(42);
+        let report = differential_test(params, 20, &RuleBackend);
+        assert!(report.positions > 10, "{}", report);
+        assert_eq!(report.unsound(), 0, "{}", report);
+    }
+}
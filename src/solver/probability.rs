@@ -0,0 +1,389 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use rayon::prelude::*;
+
+use crate::core::{Area, Location, Minefield};
+
+use super::Solver;
+
+/// Mine probabilities are not computed for positions with a larger component than this.
+pub const MAX_PROBABILITY_COMPONENT: usize = 160;
+
+/// Returns the exact probability of each cell to contain a mine, assuming every mine placement
+/// consistent with the revealed numbers is equally likely.
+///
+/// Revealed cells have probability 0 and exploded mines probability 1. Marks are ignored,
+/// marked cells are treated like hidden ones.
+///
+/// The frontier, i.e. the hidden cells next to revealed numbers, is split into independent
+/// components whose consistent configurations are enumerated. Each configuration is weighted by
+/// the number of ways to place the remaining mines on the cells next to no number.
+/// The components are enumerated in parallel.
+/// The enumeration is exponential in the size of a component, but the constraints prune it
+/// quickly on boards of the usual densities.
+///
+/// Returns `None` if a component has more cells than [`MAX_PROBABILITY_COMPONENT`] or no mine
+/// placement is consistent with the revealed numbers.
+pub fn mine_probabilities(mf: &Minefield) -> Option<Area<f64>> {
+    let fog = mf.fog();
+    let unknown: BTreeSet<_> = fog
+        .loc_iter()
+        .filter(|(_, s)| s.is_hidden() || s.is_marked())
+        .map(|(l, _)| l)
+        .collect();
+    let exploded = fog.iter().filter(|s| s.is_exploded()).count();
+    let remaining_mines = mf.mine_count().saturating_sub(exploded);
+
+    let mut solver = Solver::new(mf);
+    solver.seed();
+    let constraints: Vec<_> = solver
+        .facts
+        .iter()
+        .filter(|f| !f.proximity.is_empty() || f.count > 0)
+        .map(|f| (f.count, f.proximity.iter().collect::<Vec<_>>()))
+        .collect();
+    let frontier: BTreeSet<_> = constraints
+        .iter()
+        .flat_map(|(_, cells)| cells.iter().copied())
+        .collect();
+    let other_count = unknown.difference(&frontier).count();
+
+    let components = components(&constraints);
+    if components
+        .iter()
+        .any(|c| c.cells.len() > MAX_PROBABILITY_COMPONENT)
+    {
+        return None;
+    }
+    let components: Vec<_> = components.into_par_iter().map(|c| c.enumerate()).collect();
+
+    // Weight of a frontier configuration with `k` mines, scaled to avoid overflows.
+    let weights = placement_weights(other_count, remaining_mines, frontier.len());
+
+    let all = components
+        .iter()
+        .fold(vec![1.0], |total, c| convolve(&total, &c.by_mines));
+    let total_weight: f64 = all.iter().zip(&weights).map(|(n, w)| n * w).sum();
+    if total_weight <= 0.0 {
+        return None;
+    }
+
+    let mut probabilities = Area::<f64>::new(mf.width(), mf.height());
+    for (l, s) in fog.loc_iter() {
+        if s.is_exploded() {
+            probabilities[l] = 1.0;
+        }
+    }
+    for (i, component) in components.iter().enumerate() {
+        let rest = components
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| i != j)
+            .fold(vec![1.0], |total, (_, c)| convolve(&total, &c.by_mines));
+        // Weight of the configurations of this component with `k` mines, including every
+        // configuration of the other components and other cells.
+        let weight_with = |k: usize| -> f64 {
+            rest.iter()
+                .enumerate()
+                .map(|(r, n)| n * weights.get(k + r).copied().unwrap_or(0.0))
+                .sum()
+        };
+        for (k, mines_per_cell) in component.mines_per_cell.iter().enumerate() {
+            let weight = weight_with(k);
+            for (&l, mines) in component.cells.iter().zip(mines_per_cell) {
+                probabilities[l] += mines * weight / total_weight;
+            }
+        }
+    }
+
+    if other_count > 0 {
+        let expected_other_mines: f64 = all
+            .iter()
+            .enumerate()
+            .map(|(k, n)| n * weights[k] * remaining_mines.saturating_sub(k) as f64)
+            .sum::<f64>()
+            / total_weight;
+        for &l in unknown.difference(&frontier) {
+            probabilities[l] = expected_other_mines / other_count as f64;
+        }
+    }
+    Some(probabilities)
+}
+
+/// Returns the number of ways to place the remaining mines on `other_count` cells for each
+/// number of mines on the frontier, relative to the largest one.
+fn placement_weights(other_count: usize, remaining_mines: usize, frontier_size: usize) -> Vec<f64> {
+    let ln_binomial = |n: usize, k: usize| -> f64 {
+        let k = k.min(n - k);
+        (0..k)
+            .map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln())
+            .sum()
+    };
+    let ln_weights: Vec<_> = (0..=frontier_size)
+        .map(|k| match remaining_mines.checked_sub(k) {
+            Some(other_mines) if other_mines <= other_count => {
+                Some(ln_binomial(other_count, other_mines))
+            }
+            _ => None,
+        })
+        .collect();
+    let max = ln_weights
+        .iter()
+        .flatten()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    ln_weights
+        .into_iter()
+        .map(|w| w.map_or(0.0, |w| (w - max).exp()))
+        .collect()
+}
+
+/// Multiplies two polynomials given by their coefficients.
+fn convolve(left: &[f64], right: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; left.len() + right.len() - 1];
+    for (i, l) in left.iter().enumerate() {
+        for (j, r) in right.iter().enumerate() {
+            result[i + j] += l * r;
+        }
+    }
+    result
+}
+
+/// Frontier cells connected by shared constraints, with the constraints on them.
+struct Component {
+    cells: Vec<Location>,
+    /// Mine count and indices into `cells` of each constraint.
+    constraints: Vec<(usize, Vec<usize>)>,
+}
+
+/// The consistent configurations of a [`Component`].
+struct Enumeration {
+    cells: Vec<Location>,
+    /// Number of configurations with `k` mines at index `k`.
+    by_mines: Vec<f64>,
+    /// Number of configurations with `k` mines that have a mine on each cell.
+    mines_per_cell: Vec<Vec<f64>>,
+}
+
+/// Splits the cells of the constraints into connected components.
+fn components(constraints: &[(usize, Vec<Location>)]) -> Vec<Component> {
+    let mut constraints_of: BTreeMap<Location, Vec<usize>> = BTreeMap::new();
+    for (i, (_, cells)) in constraints.iter().enumerate() {
+        for &l in cells {
+            constraints_of.entry(l).or_default().push(i);
+        }
+    }
+
+    let mut components = Vec::new();
+    let mut visited = BTreeSet::new();
+    for &start in constraints_of.keys() {
+        if !visited.insert(start) {
+            continue;
+        }
+        // Breadth first order keeps the cells of a constraint close together,
+        // so the enumeration can prune early.
+        let mut cells = Vec::new();
+        let mut component_constraints = BTreeSet::new();
+        let mut queue = VecDeque::from(vec![start]);
+        while let Some(l) = queue.pop_front() {
+            cells.push(l);
+            for &c in &constraints_of[&l] {
+                component_constraints.insert(c);
+                for &n in &constraints[c].1 {
+                    if visited.insert(n) {
+                        queue.push_back(n);
+                    }
+                }
+            }
+        }
+        let index: HashMap<_, _> = cells.iter().enumerate().map(|(i, &l)| (l, i)).collect();
+        let constraints = component_constraints
+            .into_iter()
+            .map(|c| {
+                let (count, proximity) = &constraints[c];
+                (*count, proximity.iter().map(|l| index[l]).collect())
+            })
+            .collect();
+        components.push(Component { cells, constraints });
+    }
+    components
+}
+
+impl Component {
+    fn enumerate(self) -> Enumeration {
+        let size = self.cells.len();
+        let mut constraints_of = vec![Vec::new(); size];
+        for (i, (_, cells)) in self.constraints.iter().enumerate() {
+            for &c in cells {
+                constraints_of[c].push(i);
+            }
+        }
+        let mut state = EnumerationState {
+            constraints_of,
+            missing_mines: self.constraints.iter().map(|(count, _)| *count).collect(),
+            unassigned: self.constraints.iter().map(|(_, c)| c.len()).collect(),
+            assignment: vec![false; size],
+            by_mines: vec![0.0; size + 1],
+            mines_per_cell: vec![vec![0.0; size]; size + 1],
+        };
+        state.assign(0, 0);
+        Enumeration {
+            cells: self.cells,
+            by_mines: state.by_mines,
+            mines_per_cell: state.mines_per_cell,
+        }
+    }
+}
+
+struct EnumerationState {
+    constraints_of: Vec<Vec<usize>>,
+    missing_mines: Vec<usize>,
+    unassigned: Vec<usize>,
+    assignment: Vec<bool>,
+    by_mines: Vec<f64>,
+    mines_per_cell: Vec<Vec<f64>>,
+}
+
+impl EnumerationState {
+    fn assign(&mut self, cell: usize, mines: usize) {
+        if cell == self.assignment.len() {
+            self.by_mines[mines] += 1.0;
+            for (count, &mine) in self.mines_per_cell[mines].iter_mut().zip(&self.assignment) {
+                *count += mine as usize as f64;
+            }
+            return;
+        }
+
+        for &mine in &[false, true] {
+            let consistent = self.constraints_of[cell].iter().all(|&c| {
+                let missing = self.missing_mines[c];
+                match mine {
+                    true => missing > 0,
+                    // the other unassigned cells can still hold the missing mines
+                    false => missing < self.unassigned[c],
+                }
+            });
+            if !consistent {
+                continue;
+            }
+
+            for &c in &self.constraints_of[cell] {
+                self.unassigned[c] -= 1;
+                self.missing_mines[c] -= mine as usize;
+            }
+            self.assignment[cell] = mine;
+            self.assign(cell + 1, mines + mine as usize);
+            for &c in &self.constraints_of[cell] {
+                self.unassigned[c] += 1;
+                self.missing_mines[c] += mine as usize;
+            }
+        }
+        self.assignment[cell] = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Action, ExclusionZone, MinefieldGenerator, Parameters, PendingCommand},
+        generator::SimpleGenerator,
+    };
+
+    fn probability(probabilities: &Area<f64>, x: usize, y: usize) -> f64 {
+        probabilities[Location::new(x, y)]
+    }
+
+    #[test]
+    fn frontier_probabilities_of_simple_positions() {
+        let mf = Minefield::new_active_game(
+            "meee
+             11em
+             0011",
+        );
+        let p = mine_probabilities(&mf).unwrap();
+        assert_eq!(probability(&p, 0, 0), 0.5);
+        assert_eq!(probability(&p, 1, 0), 0.5);
+        assert_eq!(probability(&p, 2, 0), 0.0);
+        assert_eq!(probability(&p, 3, 0), 0.0);
+        assert_eq!(probability(&p, 2, 1), 0.0);
+        assert_eq!(probability(&p, 3, 1), 1.0);
+        assert_eq!(probability(&p, 0, 2), 0.0);
+    }
+
+    /// Counts the mines of every placement consistent with the revealed numbers.
+    fn brute_force(mf: &Minefield) -> Area<f64> {
+        let fog = mf.fog();
+        let unknown: Vec<_> = fog
+            .loc_iter()
+            .filter(|(_, s)| s.is_hidden())
+            .map(|(l, _)| l)
+            .collect();
+        let numbers: Vec<_> = fog
+            .loc_iter()
+            .filter_map(|(l, s)| Some((l, *s.as_revealed()?)))
+            .collect();
+
+        let mut mines_per_cell = Area::<f64>::new(mf.width(), mf.height());
+        let mut placements = 0.0;
+        let mut indices: Vec<usize> = (0..mf.mine_count()).collect();
+        loop {
+            let mines: BTreeSet<_> = indices.iter().map(|&i| unknown[i]).collect();
+            let consistent = numbers
+                .iter()
+                .all(|(l, n)| l.neighbours().filter(|n| mines.contains(n)).count() == *n);
+            if consistent {
+                placements += 1.0;
+                for &l in &mines {
+                    mines_per_cell[l] += 1.0;
+                }
+            }
+            // advance to the next combination
+            let k = indices.len();
+            match (0..k).rev().find(|&i| indices[i] < unknown.len() - k + i) {
+                Some(i) => {
+                    indices[i] += 1;
+                    for j in i + 1..k {
+                        indices[j] = indices[j - 1] + 1;
+                    }
+                }
+                None => break,
+            }
+        }
+        mines_per_cell.iter_mut().for_each(|m| *m /= placements);
+        mines_per_cell
+    }
+
+    #[test]
+    fn probabilities_match_brute_force() {
+        for seed in 0..5 {
+            let params = Parameters::new(6, 4, 5).with_seed(seed);
+            let click = Location::new(0_usize, 0_usize);
+            let zone = ExclusionZone::for_first_click(&params, click);
+            let ground = SimpleGenerator.generate(params, &zone).unwrap();
+            let mut mf = Minefield::with_ground(ground);
+            mf.execute(PendingCommand::new(click, Action::Reveal));
+
+            let expected = brute_force(&mf);
+            let actual = mine_probabilities(&mf).unwrap();
+            for ((l, e), a) in expected.loc_iter().zip(actual.iter()) {
+                assert!(
+                    (e - a).abs() < 1e-9,
+                    "seed {} at {}: {} vs {}",
+                    seed,
+                    l,
+                    e,
+                    a
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn no_probabilities_of_oversized_components() {
+        let repeats = MAX_PROBABILITY_COMPONENT / 3 + 1;
+        let board = format!("{}\n{}", "EEE".repeat(repeats), "mee".repeat(repeats));
+        let mf = Minefield::new_active_game(&board);
+        assert!(mine_probabilities(&mf).is_none());
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    iter::FromIterator,
+    ops::{BitAnd, BitOr, Sub},
+};
+
+use smallvec::SmallVec;
+
+use crate::core::Location;
+
+/// The column of a block and the index of its 64 rows within the column.
+type Block = (u32, u32);
+
+const BLOCK_ROWS: usize = 64;
+
+/// A set of locations, stored as a bitset with one 64 bit word per column and 64 rows that
+/// contain any of the locations.
+///
+/// Facts are about a few neighbouring cells, so their proximities need only a few words no
+/// matter how large the board is, and set operations are a merge of two short sorted lists.
+/// Like a `BTreeSet<Location>`, the locations are ordered by [`Location`]'s `Ord`.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Proximity {
+    /// The non-empty blocks in ascending order, so equal sets have equal blocks. The cells
+    /// around a number span three columns, so three blocks are stored inline.
+    blocks: SmallVec<[(Block, u64); 3]>,
+}
+
+impl Proximity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|(_, bits)| bits.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn contains(&self, l: &Location) -> bool {
+        let (block, bit) = position(*l);
+        self.bits(block) & bit != 0
+    }
+
+    pub fn insert(&mut self, l: Location) {
+        let (block, bit) = position(l);
+        match self.blocks.binary_search_by_key(&block, |&(b, _)| b) {
+            Ok(i) => self.blocks[i].1 |= bit,
+            Err(i) => self.blocks.insert(i, (block, bit)),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Location> + '_ {
+        self.blocks.iter().flat_map(|&((x, rows), mut bits)| {
+            std::iter::from_fn(move || {
+                let row = bits.trailing_zeros() as usize;
+                bits &= bits.checked_sub(1)?;
+                Some(Location::new(x, rows as usize * BLOCK_ROWS + row))
+            })
+        })
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len()
+            && self
+                .blocks
+                .iter()
+                .all(|&(block, bits)| other.bits(block) & bits == bits)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.first_common(other).is_none()
+    }
+
+    /// Returns the number of locations in both sets, without building the intersection.
+    pub fn intersection_len(&self, other: &Self) -> usize {
+        self.blocks
+            .iter()
+            .map(|&(block, bits)| (other.bits(block) & bits).count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the smallest location in both sets.
+    pub fn first_common(&self, other: &Self) -> Option<Location> {
+        let (mut left, mut right) = (self.blocks.iter(), other.blocks.iter());
+        let (mut l, mut r) = (left.next(), right.next());
+        while let (Some(&(lb, lbits)), Some(&(rb, rbits))) = (l, r) {
+            match lb.cmp(&rb) {
+                Ordering::Less => l = left.next(),
+                Ordering::Greater => r = right.next(),
+                Ordering::Equal if lbits & rbits != 0 => {
+                    let row = (lbits & rbits).trailing_zeros() as usize;
+                    return Some(Location::new(lb.0, lb.1 as usize * BLOCK_ROWS + row));
+                }
+                Ordering::Equal => {
+                    l = left.next();
+                    r = right.next();
+                }
+            }
+        }
+        None
+    }
+
+    fn bits(&self, block: Block) -> u64 {
+        match self.blocks.binary_search_by_key(&block, |&(b, _)| b) {
+            Ok(i) => self.blocks[i].1,
+            Err(_) => 0,
+        }
+    }
+
+    /// Combines the words of both sets block by block, a missing block counts as empty.
+    fn merge<F: Fn(u64, u64) -> u64>(&self, other: &Self, op: F) -> Self {
+        let mut blocks = SmallVec::with_capacity(self.blocks.len().max(other.blocks.len()));
+        let (mut left, mut right) = (
+            self.blocks.iter().peekable(),
+            other.blocks.iter().peekable(),
+        );
+        loop {
+            let (block, l, r) = match (left.peek(), right.peek()) {
+                (Some(&&(lb, l)), Some(&&(rb, r))) => match lb.cmp(&rb) {
+                    Ordering::Less => {
+                        left.next();
+                        (lb, l, 0)
+                    }
+                    Ordering::Greater => {
+                        right.next();
+                        (rb, 0, r)
+                    }
+                    Ordering::Equal => {
+                        left.next();
+                        right.next();
+                        (lb, l, r)
+                    }
+                },
+                (Some(&&(lb, l)), None) => {
+                    left.next();
+                    (lb, l, 0)
+                }
+                (None, Some(&&(rb, r))) => {
+                    right.next();
+                    (rb, 0, r)
+                }
+                (None, None) => break,
+            };
+            let bits = op(l, r);
+            if bits != 0 {
+                blocks.push((block, bits));
+            }
+        }
+        Self { blocks }
+    }
+}
+
+/// Returns the block of the location and its bit within the block.
+fn position(l: Location) -> (Block, u64) {
+    let coordinate = |c: Option<usize>| c.expect("Proximities only contain valid locations.");
+    let (x, y) = (coordinate(l.x.into()), coordinate(l.y.into()));
+    let block = (x as u32, (y / BLOCK_ROWS) as u32);
+    (block, 1 << (y % BLOCK_ROWS))
+}
+
+impl BitAnd for &Proximity {
+    type Output = Proximity;
+
+    fn bitand(self, other: Self) -> Proximity {
+        self.merge(other, |l, r| l & r)
+    }
+}
+
+impl BitOr for &Proximity {
+    type Output = Proximity;
+
+    fn bitor(self, other: Self) -> Proximity {
+        self.merge(other, |l, r| l | r)
+    }
+}
+
+impl Sub for &Proximity {
+    type Output = Proximity;
+
+    fn sub(self, other: Self) -> Proximity {
+        self.merge(other, |l, r| l & !r)
+    }
+}
+
+impl FromIterator<Location> for Proximity {
+    fn from_iter<I: IntoIterator<Item = Location>>(iter: I) -> Self {
+        let mut proximity = Self::new();
+        for l in iter {
+            proximity.insert(l);
+        }
+        proximity
+    }
+}
+
+impl Ord for Proximity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl PartialOrd for Proximity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for Proximity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{ ")?;
+        for l in self.iter() {
+            write!(f, "{}, ", l)?;
+        }
+        write!(f, "}} ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn both(cells: &[(usize, usize)]) -> (Proximity, BTreeSet<Location>) {
+        let locations = cells.iter().map(|&l| Location::from(l));
+        (locations.clone().collect(), locations.collect())
+    }
+
+    #[test]
+    fn proximity_behaves_like_btree_set() {
+        // Rows 63 and 64 are in different blocks of the same column.
+        let (a, a_set) = both(&[(0, 0), (1, 0), (1, 63), (1, 64), (70, 2)]);
+        let (b, b_set) = both(&[(1, 0), (1, 64), (2, 5)]);
+
+        let sorted = |p: &Proximity| p.iter().collect::<Vec<_>>();
+        assert_eq!(sorted(&a), a_set.iter().copied().collect::<Vec<_>>());
+        assert_eq!(a.len(), 5);
+        assert_eq!(
+            sorted(&(&a & &b)),
+            (&a_set & &b_set).into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            sorted(&(&a | &b)),
+            (&a_set | &b_set).into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            sorted(&(&a - &b)),
+            (&a_set - &b_set).into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(a.intersection_len(&b), 2);
+        assert_eq!(a.first_common(&b), Some(Location::new(1_usize, 0_usize)));
+        assert!(a.contains(&Location::new(1_usize, 64_usize)));
+        assert!(!a.contains(&Location::new(2_usize, 5_usize)));
+
+        assert!((&a & &b).is_subset(&a));
+        assert!(!b.is_subset(&a));
+        assert!((&a - &b).is_disjoint(&b));
+        assert_eq!(a.cmp(&b), a_set.cmp(&b_set));
+        assert_eq!(&(&a - &a), &Proximity::new());
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+
+use crate::core::{Area, Location, Minefield, State};
+
+use super::{Analysis, FactStore, Solver};
+
+/// Keeps the facts of the [`Solver`] between the moves of one game.
+///
+/// A move only changes a few cells, so instead of seeding and deducing everything again, an
+/// update retracts the facts about cells that were revealed since the last update, seeds the
+/// numbers around them and only combines these new facts with the kept ones. The results are
+/// the same as those of [`analyze`](super::analyze).
+///
+/// If the position does not follow from the last one, e.g. because a new game was started,
+/// the session starts over.
+#[derive(Debug, Default)]
+pub struct SolverSession {
+    facts: FactStore,
+    iteration: usize,
+    /// The position of the last update.
+    fog: Option<Area<State>>,
+    mine_count: usize,
+}
+
+impl SolverSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Analyzes the mine field with the default rules, reusing the facts of the last update.
+    ///
+    /// The number of iterations in the analysis counts all iterations of the session.
+    pub fn update(&mut self, mf: &Minefield) -> Analysis {
+        let mut solver = Solver::new(mf);
+        solver.seed_rules();
+        match self.revealed_since_last_update(mf) {
+            Some(revealed) => {
+                solver.facts = std::mem::take(&mut self.facts);
+                solver.iteration = self.iteration;
+                solver.retract(&revealed);
+                let numbers: HashSet<_> = revealed
+                    .iter()
+                    .flat_map(|l| l.neighbours().chain(std::iter::once(*l)))
+                    .collect();
+                solver.seed_numbers(numbers);
+                solver.deduce_seeded();
+            }
+            None => solver.deduce(),
+        }
+
+        let analysis = solver.analysis();
+        self.facts = solver.facts;
+        self.iteration = solver.iteration;
+        self.fog = Some(mf.fog().clone());
+        self.mine_count = mf.mine_count();
+        analysis
+    }
+
+    /// Returns the cells that were hidden or marked at the last update and are not anymore, or
+    /// `None` if the mine field cannot have been reached from the last position, e.g. because a
+    /// revealed cell shows a different number.
+    fn revealed_since_last_update(&self, mf: &Minefield) -> Option<HashSet<Location>> {
+        let previous = self.fog.as_ref()?;
+        let fog = mf.fog();
+        let same_board = previous.width() == fog.width()
+            && previous.height() == fog.height()
+            && self.mine_count == mf.mine_count();
+        if !same_board {
+            return None;
+        }
+
+        let unknown = |s: &State| s.is_hidden() || s.is_marked();
+        let mut revealed = HashSet::new();
+        for ((l, before), now) in previous.loc_iter().zip(fog.iter()) {
+            match (unknown(before), unknown(now)) {
+                (true, false) => {
+                    revealed.insert(l);
+                }
+                (false, true) => return None,
+                (false, false) if before != now => return None,
+                _ => {}
+            }
+        }
+        Some(revealed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Action, GameState, Parameters, PendingCommand},
+        solver::{analyze, random_positions},
+    };
+
+    #[test]
+    fn session_matches_fresh_analysis_after_every_move() {
+        let params = Parameters::new(9, 9, 10).with_seed(11);
+        for mut game in random_positions(params, 5) {
+            let mut session = SolverSession::new();
+            while let GameState::InProgress { .. } = game.state() {
+                let analysis = session.update(&game);
+                let fresh = analyze(&game);
+                assert_eq!(
+                    (&analysis.safe, &analysis.mines),
+                    (&fresh.safe, &fresh.mines),
+                    "{}",
+                    game
+                );
+
+                let is_hidden = |l: &Location| game.fog()[*l].is_hidden();
+                let next = match analysis.safe.iter().copied().filter(is_hidden).min() {
+                    Some(l) => PendingCommand::new(l, Action::Reveal),
+                    None => match analysis.mines.iter().copied().filter(is_hidden).min() {
+                        Some(l) => PendingCommand::new(l, Action::Mark),
+                        None => break,
+                    },
+                };
+                game.execute(next);
+            }
+        }
+    }
+
+    #[test]
+    fn session_starts_over_on_new_game() {
+        let mut session = SolverSession::new();
+        let first = Minefield::new_active_game("m1\n11");
+        assert_eq!(session.update(&first).mines.len(), 1);
+
+        let second = Minefield::new_active_game("1m\n11");
+        let analysis = session.update(&second);
+        assert_eq!(analysis.mines, analyze(&second).mines);
+        assert!(analysis.safe.is_empty());
+    }
+
+    #[test]
+    fn session_starts_over_if_a_number_changes() {
+        let mut session = SolverSession::new();
+        let first = Minefield::new_active_game("m1e\neee\neem");
+        assert!(session.update(&first).safe.is_empty());
+
+        // the same cells are revealed, but the number is different
+        let second = Minefield::new_active_game("m2m\neee\neee");
+        let analysis = session.update(&second);
+        assert_eq!(analysis.safe, analyze(&second).safe);
+        assert_eq!(analysis.safe.len(), 3);
+    }
+}